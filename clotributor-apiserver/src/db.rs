@@ -24,6 +24,15 @@ pub(crate) trait DB {
     /// Get filters that can be used when searching for issues.
     async fn get_issues_filters(&self) -> Result<JsonString>;
 
+    /// Get projects looking for maintainers that match the criteria provided.
+    async fn get_projects_maintainers_wanted(
+        &self,
+        input: &MaintainersWantedInput,
+    ) -> Result<JsonString>;
+
+    /// Get status of the latest tracker and registrar runs.
+    async fn get_status(&self) -> Result<JsonString>;
+
     /// Search issues that match the criteria provided.
     async fn search_issues(&self, input: &SearchIssuesInput) -> Result<(Count, JsonString)>;
 }
@@ -51,6 +60,27 @@ impl DB for PgDB {
         Ok(filters)
     }
 
+    async fn get_projects_maintainers_wanted(
+        &self,
+        input: &MaintainersWantedInput,
+    ) -> Result<JsonString> {
+        let db = self.pool.get().await?;
+        let projects = db
+            .query_one(
+                "select get_projects_maintainers_wanted($1::jsonb)::text",
+                &[&Json(input)],
+            )
+            .await?
+            .get(0);
+        Ok(projects)
+    }
+
+    async fn get_status(&self) -> Result<JsonString> {
+        let db = self.pool.get().await?;
+        let status = db.query_one("select get_status()::text", &[]).await?.get(0);
+        Ok(status)
+    }
+
     async fn search_issues(&self, input: &SearchIssuesInput) -> Result<(Count, JsonString)> {
         let db = self.pool.get().await?;
         let row = db
@@ -65,6 +95,13 @@ impl DB for PgDB {
     }
 }
 
+/// Query input used when listing projects looking for maintainers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MaintainersWantedInput {
+    pub foundation: Option<Vec<String>>,
+    pub language: Option<Vec<String>>,
+}
+
 /// Query input used when searching for issues.
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub(crate) struct SearchIssuesInput {
@@ -73,13 +110,594 @@ pub(crate) struct SearchIssuesInput {
     pub sort_by: Option<String>,
     pub foundation: Option<Vec<String>>,
     pub maturity: Option<Vec<String>>,
+    pub category: Option<Vec<String>>,
+    pub subcategory: Option<Vec<String>>,
     pub project: Option<Vec<String>>,
     pub area: Option<Vec<String>>,
     pub kind: Option<Vec<String>>,
     pub difficulty: Option<Vec<String>>,
+    pub estimated_difficulty: Option<Vec<String>>,
     pub language: Option<Vec<String>>,
+    pub language_min_share: Option<f64>,
+    pub primary_language: Option<Vec<String>>,
+    pub skill: Option<Vec<String>>,
+    #[serde(rename = "type")]
+    pub issue_type: Option<Vec<String>>,
     pub mentor_available: Option<bool>,
     pub good_first_issue: Option<bool>,
     pub ts_query_web: Option<String>,
     pub no_linked_prs: Option<bool>,
+    pub hide_inactive: Option<bool>,
+    pub include_duplicates: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn get_issues_filters() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+
+        let filters: Value = serde_json::from_str(&db.get_issues_filters().await.unwrap()).unwrap();
+        assert_eq!(
+            filters["filters"][0],
+            json!({
+                "title": "Foundation",
+                "key": "foundation",
+                "options": [{"name": "CNCF", "value": "cncf"}]
+            })
+        );
+        assert_eq!(
+            filters["filters"][2],
+            json!({
+                "title": "Category",
+                "key": "category",
+                "options": [{"name": "app definition", "value": "app definition"}]
+            })
+        );
+        assert_eq!(
+            filters["filters"][3],
+            json!({
+                "title": "Subcategory",
+                "key": "subcategory",
+                "options": [{"name": "image build", "value": "image build"}]
+            })
+        );
+        assert_eq!(
+            filters["filters"][4],
+            json!({
+                "title": "Project",
+                "key": "project",
+                "options": [{"name": "Project", "value": "project"}]
+            })
+        );
+        assert_eq!(
+            filters["filters"][8],
+            json!({
+                "title": "Type",
+                "key": "type",
+                "options": [
+                    {"name": "Issue", "value": "issue"},
+                    {"name": "Pull Request", "value": "pull_request"},
+                    {"name": "Discussion", "value": "discussion"}
+                ]
+            })
+        );
+        assert_eq!(
+            filters["filters"][9],
+            json!({
+                "title": "Language",
+                "key": "language",
+                "options": [
+                    {"name": "language1", "value": "language1"},
+                    {"name": "language2", "value": "language2"}
+                ]
+            })
+        );
+        assert_eq!(
+            filters["extra"],
+            json!({
+                "maturity": {"cncf": ["sandbox"]},
+                "subcategory": {"app definition": ["image build"]},
+                "project": {"cncf": ["project"]}
+            })
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn search_issues() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+
+        let input = SearchIssuesInput {
+            foundation: Some(vec!["cncf".to_string()]),
+            maturity: Some(vec!["sandbox".to_string()]),
+            category: Some(vec!["app definition".to_string()]),
+            subcategory: Some(vec!["image build".to_string()]),
+            project: Some(vec!["project".to_string()]),
+            area: Some(vec!["docs".to_string()]),
+            kind: Some(vec!["bug".to_string()]),
+            difficulty: Some(vec!["easy".to_string()]),
+            language: Some(vec!["language1".to_string()]),
+            issue_type: Some(vec!["issue".to_string()]),
+            mentor_available: Some(true),
+            good_first_issue: Some(true),
+            ts_query_web: Some("issue1".to_string()),
+            no_linked_prs: Some(true),
+            hide_inactive: Some(true),
+            ..Default::default()
+        };
+        let (count, issues) = db.search_issues(&input).await.unwrap();
+        let issues: Value = serde_json::from_str(&issues).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            issues,
+            json!([{
+                "number": 1,
+                "title": "issue1",
+                "url": "https://github.com/org/repo1/issues/1",
+                "labels": ["label1", "label2"],
+                "published_at": 1_672_628_645,
+                "area": "docs",
+                "kind": "bug",
+                "difficulty": "easy",
+                "mentor_available": true,
+                "good_first_issue": true,
+                "skills": ["language1", "language2"],
+                "has_linked_prs": false,
+                "type": "issue",
+                "repository": {
+                    "name": "repo1",
+                    "url": "https://github.com/org/repo1",
+                    "homepage_url": "https://homepage.url",
+                    "contributing_url": "https://contributing.url",
+                    "code_of_conduct_url": "https://coc.url",
+                    "license": "Apache-2.0",
+                    "default_branch": "main",
+                    "archived": false,
+                    "disabled": false,
+                    "median_first_response_secs": 3600,
+                    "open_pull_requests": 2,
+                    "topics": ["topic1"],
+                    "languages": ["language1", "language2"],
+                    "language_shares": {"language1": 95.5, "language2": 4.5},
+                    "primary_language": "language1",
+                    "stars": 10
+                },
+                "project": {
+                    "name": "project",
+                    "display_name": "Project",
+                    "logo_url": "https://logo.url",
+                    "logo_dark_url": "https://logo.dark.url",
+                    "devstats_url": "https://devstats.url",
+                    "accepted_at": "2023-01-02",
+                    "maturity": "sandbox",
+                    "category": "app definition",
+                    "subcategory": "image build",
+                    "maintainers_wanted": {"enabled": true},
+                    "foundation": "cncf"
+                },
+                "_relevance": 0.12
+            }])
+        );
+
+        let input = SearchIssuesInput {
+            issue_type: Some(vec!["pull_request".to_string()]),
+            ..Default::default()
+        };
+        let (count, issues) = db.search_issues(&input).await.unwrap();
+        assert_eq!(count, 0);
+        assert_eq!(issues, "[]");
+
+        // Issues from archived repositories are hidden when requested
+        let client = db.pool.get().await.unwrap();
+        client
+            .execute("update repository set archived = true", &[])
+            .await
+            .unwrap();
+        let input = SearchIssuesInput {
+            hide_inactive: Some(true),
+            ..Default::default()
+        };
+        let (count, _) = db.search_issues(&input).await.unwrap();
+        assert_eq!(count, 0);
+        let (count, _) = db
+            .search_issues(&SearchIssuesInput::default())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn search_issues_by_language() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+
+        // Issues can be filtered by the repository's primary language or by
+        // the minimum share of the languages selected
+        for (language, language_min_share, primary_language, expected_count) in [
+            (None, None, Some("language1"), 1),
+            (None, None, Some("language2"), 0),
+            (Some("language2"), None, None, 1),
+            (Some("language2"), Some(4.0), None, 1),
+            (Some("language2"), Some(10.0), None, 0),
+        ] {
+            let input = SearchIssuesInput {
+                language: language.map(|l| vec![l.to_string()]),
+                language_min_share,
+                primary_language: primary_language.map(|l| vec![l.to_string()]),
+                ..Default::default()
+            };
+            let (count, _) = db.search_issues(&input).await.unwrap();
+            assert_eq!(count, expected_count);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn search_issues_by_skill() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+        let search_count = |skill: &'static str| {
+            let input = SearchIssuesInput {
+                skill: Some(vec![skill.to_string()]),
+                ..Default::default()
+            };
+            let db = &db;
+            async move { db.search_issues(&input).await.unwrap().0 }
+        };
+
+        // Repository languages are used when the issue has no skills
+        assert_eq!(search_count("language1").await, 1);
+        assert_eq!(search_count("Go").await, 0);
+
+        // Issue skills are used when available
+        let client = db.pool.get().await.unwrap();
+        client
+            .execute("update issue set skills = '{Go}'", &[])
+            .await
+            .unwrap();
+        assert_eq!(search_count("language1").await, 0);
+        assert_eq!(search_count("Go").await, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn search_issues_by_estimated_difficulty() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+        let search_count = |estimated_difficulty: &'static str| {
+            let input = SearchIssuesInput {
+                estimated_difficulty: Some(vec![estimated_difficulty.to_string()]),
+                ..Default::default()
+            };
+            let db = &db;
+            async move { db.search_issues(&input).await.unwrap().0 }
+        };
+
+        // The explicit difficulty is used when available
+        assert_eq!(search_count("easy").await, 1);
+        assert_eq!(search_count("hard").await, 0);
+
+        // Otherwise the estimated one is used
+        let client = db.pool.get().await.unwrap();
+        client
+            .execute(
+                "update issue set difficulty = null, estimated_difficulty = 'hard'",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(search_count("easy").await, 0);
+        assert_eq!(search_count("hard").await, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn search_issues_duplicates_collapsed() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "
+                insert into issue (
+                    issue_id,
+                    title,
+                    url,
+                    number,
+                    labels,
+                    tsdoc,
+                    canonical_issue_id,
+                    repository_id
+                ) values (
+                    2,
+                    'issue1',
+                    'https://github.com/org/repo2/issues/2',
+                    2,
+                    '{}',
+                    to_tsvector('issue1'),
+                    1,
+                    '00000000-0000-0001-0000-000000000000'
+                );
+                ",
+            )
+            .await
+            .unwrap();
+
+        // Duplicates are collapsed into their canonical issue by default
+        let (count, issues) = db
+            .search_issues(&SearchIssuesInput::default())
+            .await
+            .unwrap();
+        let issues: Value = serde_json::from_str(&issues).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            issues[0]["duplicates"],
+            json!(["https://github.com/org/repo2/issues/2"])
+        );
+
+        // Unless they are requested explicitly
+        let input = SearchIssuesInput {
+            include_duplicates: Some(true),
+            ..Default::default()
+        };
+        let (count, _) = db.search_issues(&input).await.unwrap();
+        assert_eq!(count, 2);
+
+        // Duplicates are displayed when the canonical issue doesn't match the
+        // filters provided
+        client
+            .execute("update issue set skills = '{Go}' where issue_id = 2", &[])
+            .await
+            .unwrap();
+        let input = SearchIssuesInput {
+            skill: Some(vec!["Go".to_string()]),
+            ..Default::default()
+        };
+        let (count, issues) = db.search_issues(&input).await.unwrap();
+        let issues: Value = serde_json::from_str(&issues).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            issues[0]["url"],
+            json!("https://github.com/org/repo2/issues/2")
+        );
+
+        // Duplicates are displayed when the canonical issue is not available
+        client
+            .execute("delete from issue where issue_id = 1", &[])
+            .await
+            .unwrap();
+        let (count, _) = db
+            .search_issues(&SearchIssuesInput::default())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn get_projects_maintainers_wanted() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(
+                r#"
+                update project set maintainers_wanted = '{
+                    "enabled": true,
+                    "links": [{"title": "Contributing", "url": "https://contributing.url"}],
+                    "contacts": [{"github_handle": "user1"}]
+                }';
+                insert into project (name, description, maintainers_wanted, foundation_id)
+                values ('project2', 'Project 2 description', '{"enabled": false}', 'cncf');
+                "#,
+            )
+            .await
+            .unwrap();
+
+        let input = MaintainersWantedInput {
+            foundation: Some(vec!["cncf".to_string()]),
+            language: Some(vec!["language2".to_string()]),
+        };
+        let projects: Value =
+            serde_json::from_str(&db.get_projects_maintainers_wanted(&input).await.unwrap())
+                .unwrap();
+        assert_eq!(
+            projects,
+            json!([{
+                "name": "project",
+                "display_name": "Project",
+                "description": "Project description",
+                "logo_url": "https://logo.url",
+                "logo_dark_url": "https://logo.dark.url",
+                "maturity": "sandbox",
+                "foundation": "cncf",
+                "languages": ["language1", "language2"],
+                "links": [{"title": "Contributing", "url": "https://contributing.url"}],
+                "contacts": [{"github_handle": "user1"}]
+            }])
+        );
+
+        let input = MaintainersWantedInput {
+            language: Some(vec!["language3".to_string()]),
+            ..Default::default()
+        };
+        let projects = db.get_projects_maintainers_wanted(&input).await.unwrap();
+        assert_eq!(projects, "[]");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn get_status() {
+        let db = setup_test_db().await;
+        seed_issue(&db).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "
+                select register_run('tracker', null, '2023-01-02T03:04:05Z', 1, '{}', null);
+                select register_run('registrar', 'cncf', '2023-01-02T03:04:05Z', 1, '{error}', '{failure}');
+                ",
+            )
+            .await
+            .unwrap();
+
+        let status: Value = serde_json::from_str(&db.get_status().await.unwrap()).unwrap();
+        assert_eq!(status["tracker"]["started_at"], 1_672_628_645);
+        assert_eq!(status["tracker"]["status"], "succeeded");
+        assert_eq!(status["tracker"]["processed"], 1);
+        assert!(status["tracker"]["finished_at"].is_number());
+        assert_eq!(status["foundations"][0]["foundation"], "cncf");
+        assert_eq!(status["foundations"][0]["display_name"], "CNCF");
+        assert_eq!(status["foundations"][0]["registrar"]["status"], "failed");
+        assert_eq!(
+            status["foundations"][0]["registrar"]["errors"],
+            json!(["error"])
+        );
+        assert_eq!(
+            status["foundations"][0]["registrar"]["failures"],
+            json!(["failure"])
+        );
+        assert!(status["foundations"][0]["inactive_repositories"].is_null());
+
+        // Archived or disabled repositories are listed as inactive
+        client
+            .execute("update repository set disabled = true", &[])
+            .await
+            .unwrap();
+        let status: Value = serde_json::from_str(&db.get_status().await.unwrap()).unwrap();
+        assert_eq!(
+            status["foundations"][0]["inactive_repositories"],
+            json!([{
+                "url": "https://github.com/org/repo1",
+                "archived": false,
+                "disabled": true
+            }])
+        );
+    }
+
+    /// Setup a new database for a test (see clotributor_testutils).
+    async fn setup_test_db() -> PgDB {
+        PgDB::new(clotributor_testutils::setup_test_db().await)
+    }
+
+    /// Seed the database with a sample issue (and its foundation, project and
+    /// repository).
+    #[allow(clippy::too_many_lines)]
+    async fn seed_issue(db: &PgDB) {
+        clotributor_testutils::seed_foundation(&db.pool).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "
+                insert into project (
+                    project_id,
+                    name,
+                    display_name,
+                    description,
+                    logo_url,
+                    logo_dark_url,
+                    devstats_url,
+                    accepted_at,
+                    maturity,
+                    category,
+                    subcategory,
+                    maintainers_wanted,
+                    foundation_id
+                ) values (
+                    '00000000-0001-0000-0000-000000000000',
+                    'project',
+                    'Project',
+                    'Project description',
+                    'https://logo.url',
+                    'https://logo.dark.url',
+                    'https://devstats.url',
+                    '2023-01-02',
+                    'sandbox',
+                    'app definition',
+                    'image build',
+                    '{\"enabled\": true}',
+                    'cncf'
+                );
+
+                insert into repository (
+                    repository_id,
+                    name,
+                    url,
+                    homepage_url,
+                    contributing_url,
+                    code_of_conduct_url,
+                    license,
+                    default_branch,
+                    median_first_response_secs,
+                    open_pull_requests,
+                    topics,
+                    languages,
+                    language_shares,
+                    primary_language,
+                    stars,
+                    project_id
+                ) values (
+                    '00000000-0000-0001-0000-000000000000',
+                    'repo1',
+                    'https://github.com/org/repo1',
+                    'https://homepage.url',
+                    'https://contributing.url',
+                    'https://coc.url',
+                    'Apache-2.0',
+                    'main',
+                    3600,
+                    2,
+                    '{topic1}',
+                    '{language1,language2}',
+                    '{\"language1\": 95.5, \"language2\": 4.5}',
+                    'language1',
+                    10,
+                    '00000000-0001-0000-0000-000000000000'
+                );
+
+                insert into issue (
+                    issue_id,
+                    title,
+                    url,
+                    number,
+                    labels,
+                    area,
+                    kind,
+                    difficulty,
+                    mentor_available,
+                    good_first_issue,
+                    has_linked_prs,
+                    published_at,
+                    tsdoc,
+                    repository_id
+                ) values (
+                    1,
+                    'issue1',
+                    'https://github.com/org/repo1/issues/1',
+                    1,
+                    '{label1,label2}',
+                    'docs',
+                    'bug',
+                    'easy',
+                    true,
+                    true,
+                    false,
+                    '2023-01-02T03:04:05Z',
+                    setweight(to_tsvector('project'), 'A') ||
+                    setweight(to_tsvector('issue1 label1 label2'), 'C'),
+                    '00000000-0000-0001-0000-000000000000'
+                );
+                ",
+            )
+            .await
+            .unwrap();
+    }
 }
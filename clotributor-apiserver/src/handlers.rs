@@ -22,7 +22,7 @@ use tower_http::{
 };
 use tracing::error;
 
-use crate::db::{DynDB, SearchIssuesInput};
+use crate::db::{DynDB, MaintainersWantedInput, SearchIssuesInput};
 
 /// Index HTML document cache duration.
 const INDEX_CACHE_MAX_AGE: usize = 300;
@@ -59,6 +59,11 @@ pub(crate) fn setup_router(cfg: &Arc<Config>, db: DynDB) -> Result<Router> {
     let router = Router::new()
         .route("/api/filters/issues", get(issues_filters))
         .route("/api/issues/search", get(search_issues))
+        .route(
+            "/api/projects/maintainers-wanted",
+            get(projects_maintainers_wanted),
+        )
+        .route("/api/status", get(status))
         .route("/", get_service(index.clone()))
         .nest_service(
             "/static",
@@ -105,6 +110,41 @@ async fn search_issues(State(db): State<DynDB>, RawQuery(query): RawQuery) -> im
         .map_err(internal_error)
 }
 
+/// Handler that returns the projects looking for maintainers.
+async fn projects_maintainers_wanted(
+    State(db): State<DynDB>,
+    RawQuery(query): RawQuery,
+) -> impl IntoResponse {
+    // Get projects looking for maintainers from database
+    let query = query.unwrap_or_default();
+    let input: MaintainersWantedInput =
+        serde_qs::from_str(&query).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let projects = db
+        .get_projects_maintainers_wanted(&input)
+        .await
+        .map_err(internal_error)?;
+
+    // Return projects as json
+    Response::builder()
+        .header(CACHE_CONTROL, format!("max-age={DEFAULT_API_MAX_AGE}"))
+        .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+        .body(Body::from(projects))
+        .map_err(internal_error)
+}
+
+/// Handler that returns the status of the latest tracker and registrar runs.
+async fn status(State(db): State<DynDB>) -> impl IntoResponse {
+    // Get status from database
+    let status = db.get_status().await.map_err(internal_error)?;
+
+    // Return status as json
+    Response::builder()
+        .header(CACHE_CONTROL, format!("max-age={DEFAULT_API_MAX_AGE}"))
+        .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+        .body(Body::from(status))
+        .map_err(internal_error)
+}
+
 /// Helper for mapping any error into a `500 Internal Server Error` response.
 #[allow(clippy::needless_pass_by_value)]
 fn internal_error<E>(err: E) -> StatusCode
@@ -157,6 +197,47 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn get_projects_maintainers_wanted() {
+        let mut db = MockDB::new();
+        db.expect_get_projects_maintainers_wanted()
+            .with(eq(MaintainersWantedInput {
+                foundation: Some(vec!["cncf".to_string()]),
+                language: Some(vec!["go".to_string(), "rust".to_string()]),
+            }))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(r#"[{"project": "info"}]"#.to_string()))));
+
+        let response = setup_test_router(db)
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri(
+                        "\
+                        /api/projects/maintainers-wanted?\
+                            foundation[0]=cncf&\
+                            language[0]=go&\
+                            language[1]=rust\
+                        ",
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[CACHE_CONTROL],
+            format!("max-age={DEFAULT_API_MAX_AGE}")
+        );
+        assert_eq!(response.headers()[CONTENT_TYPE], APPLICATION_JSON.as_ref());
+        assert_eq!(
+            to_bytes(response.into_body(), usize::MAX).await.unwrap(),
+            r#"[{"project": "info"}]"#.to_string(),
+        );
+    }
+
     #[tokio::test]
     async fn search_issues() {
         let mut db = MockDB::new();
@@ -167,15 +248,24 @@ mod tests {
                 sort_by: Some("most_recent".to_string()),
                 foundation: Some(vec!["cncf".to_string()]),
                 maturity: Some(vec!["graduated".to_string(), "incubating".to_string()]),
+                category: Some(vec!["provisioning".to_string()]),
+                subcategory: Some(vec!["security".to_string()]),
                 project: Some(vec!["artifacthub".to_string()]),
                 area: Some(vec!["docs".to_string()]),
                 kind: Some(vec!["bug".to_string()]),
                 difficulty: Some(vec!["easy".to_string()]),
+                estimated_difficulty: Some(vec!["medium".to_string()]),
                 language: Some(vec!["rust".to_string()]),
+                language_min_share: Some(10.5),
+                primary_language: Some(vec!["go".to_string()]),
+                skill: Some(vec!["networking".to_string()]),
+                issue_type: Some(vec!["pull_request".to_string()]),
                 mentor_available: Some(true),
                 good_first_issue: Some(true),
                 ts_query_web: Some("text".to_string()),
                 no_linked_prs: Some(true),
+                hide_inactive: Some(true),
+                include_duplicates: Some(true),
             }))
             .times(1)
             .returning(|_| Box::pin(future::ready(Ok((1, r#"[{"issue": "info"}]"#.to_string())))));
@@ -193,14 +283,23 @@ mod tests {
                             foundation[0]=cncf&\
                             maturity[0]=graduated&\
                             maturity[1]=incubating&\
+                            category[0]=provisioning&\
+                            subcategory[0]=security&\
                             project[0]=artifacthub&\
                             area[0]=docs&\
                             kind[0]=bug&\
                             difficulty[0]=easy&\
+                            estimated_difficulty[0]=medium&\
                             language[0]=rust&\
+                            language_min_share=10.5&\
+                            primary_language[0]=go&\
+                            skill[0]=networking&\
+                            type[0]=pull_request&\
                             mentor_available=true&\
                             good_first_issue=true&\
                             no_linked_prs=true&\
+                            hide_inactive=true&\
+                            include_duplicates=true&\
                             ts_query_web=text&\
                         ",
                     )
@@ -223,6 +322,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn status() {
+        let mut db = MockDB::new();
+        db.expect_get_status()
+            .times(1)
+            .returning(|| Box::pin(future::ready(Ok(r#"{"some": "status"}"#.to_string()))));
+
+        let response = setup_test_router(db)
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/api/status")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[CACHE_CONTROL],
+            format!("max-age={DEFAULT_API_MAX_AGE}")
+        );
+        assert_eq!(response.headers()[CONTENT_TYPE], APPLICATION_JSON.as_ref());
+        assert_eq!(
+            to_bytes(response.into_body(), usize::MAX).await.unwrap(),
+            r#"{"some": "status"}"#.to_string(),
+        );
+    }
+
     fn setup_test_router(db: MockDB) -> Router {
         let cfg = Config::builder()
             .set_default("apiserver.staticPath", "")
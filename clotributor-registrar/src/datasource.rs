@@ -0,0 +1,707 @@
+use std::{path::Path, sync::LazyLock};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use postgres_types::{FromSql, ToSql};
+use regex::Regex;
+use reqwest::{StatusCode, header};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::registrar::{Project, Repository};
+
+/// Prefix used by the urls that reference local data files.
+const FILE_URL_PREFIX: &str = "file://";
+
+/// Version of the process that builds the projects from the data files. It
+/// must be bumped when a change affects the projects built from a data file
+/// that has not changed (e.g. a new field is extracted from it), so that the
+/// data files cached using a previous version are processed again.
+pub(crate) const DATA_CACHE_VERSION: i32 = 1;
+
+/// Path (relative to the landscape data file location) where the landscape
+/// logos are hosted.
+const LANDSCAPE_LOGOS_PATH: &str = "hosted_logos";
+
+/// Regular expression used to extract the project's name from a CLOMonitor
+/// data file entry that is not valid YAML.
+static ENTRY_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^(?:- +| {2})name: *["']?([^"'#\s][^"'#]*?)["']? *(?:#.*)?$"#)
+        .expect("exprs in ENTRY_NAME to be valid")
+});
+
+/// Format of a foundation's data file.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSql, FromSql, ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+#[postgres(name = "data_format")]
+pub(crate) enum DataFormat {
+    /// CLOMonitor style YAML list of projects.
+    #[default]
+    #[postgres(name = "clomonitor")]
+    Clomonitor,
+    /// JSON list of projects, using the same schema as the CLOMonitor format.
+    #[postgres(name = "json")]
+    Json,
+    /// CNCF landscape.yml file.
+    #[postgres(name = "landscape")]
+    Landscape,
+}
+
+/// Information about the last data file processed successfully, used to
+/// avoid processing it again when it has not changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DataFileCache {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub digest: Option<String>,
+    pub version: Option<i32>,
+}
+
+/// Data file fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DataFile {
+    pub content: String,
+    pub cache: DataFileCache,
+}
+
+/// Fetch the content of the data file located at the url provided. Local
+/// data files can be referenced using a file:// url or an absolute path.
+///
+/// When the data file has not changed since it was processed last time (as
+/// reported by the server using the cache information provided, or because
+/// its digest has not changed), None is returned. Cache information from a
+/// different data cache version is ignored.
+pub(crate) async fn fetch_data_file(
+    http_client: &reqwest::Client,
+    data_url: &str,
+    cache: &DataFileCache,
+) -> Result<Option<DataFile>> {
+    let no_cache = DataFileCache::default();
+    let cache = if cache.version == Some(DATA_CACHE_VERSION) {
+        cache
+    } else {
+        &no_cache
+    };
+    let mut data_file = DataFile {
+        content: String::new(),
+        cache: DataFileCache {
+            version: Some(DATA_CACHE_VERSION),
+            ..Default::default()
+        },
+    };
+
+    if data_url.starts_with("http://") || data_url.starts_with("https://") {
+        // Fetch remote data file (if it has been modified)
+        let mut req = http_client.get(data_url);
+        if let Some(etag) = &cache.etag {
+            req = req.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cache.last_modified {
+            req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let resp = req.send().await?;
+        match resp.status() {
+            StatusCode::OK => {}
+            StatusCode::NOT_MODIFIED => return Ok(None),
+            status => bail!("unexpected status code getting data file: {status}"),
+        }
+        let header_value = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        data_file.cache.etag = header_value(header::ETAG);
+        data_file.cache.last_modified = header_value(header::LAST_MODIFIED);
+        data_file.content = resp.text().await?;
+    } else {
+        // Read local data file
+        let path = local_data_file_path(data_url)?;
+        data_file.content = tokio::fs::read_to_string(path)
+            .await
+            .context(format!("error reading data file {path}"))?;
+    }
+
+    // Check if the data file content has changed
+    let digest = hex::encode(Sha256::digest(&data_file.content));
+    if cache.digest.as_ref() == Some(&digest) {
+        return Ok(None);
+    }
+    data_file.cache.digest = Some(digest);
+
+    Ok(Some(data_file))
+}
+
+/// Return the path of the local data file referenced by the url provided,
+/// which must be a file:// url or an absolute path. Urls using any other
+/// scheme (or a misspelled one, like htps://) are rejected.
+fn local_data_file_path(data_url: &str) -> Result<&str> {
+    if let Some(path) = data_url.strip_prefix(FILE_URL_PREFIX) {
+        return Ok(path);
+    }
+    if !Path::new(data_url).is_absolute() {
+        bail!(
+            "invalid data url {data_url} (expected an http(s) or file:// url, or an absolute path)"
+        );
+    }
+    Ok(data_url)
+}
+
+/// Project entry available in a data file.
+#[derive(Debug)]
+pub(crate) struct DataFileEntry {
+    /// Line of the data file where the entry starts (when available).
+    pub line: Option<usize>,
+    /// Project name, when it could be extracted from the entry.
+    pub name: Option<String>,
+    /// Project parsed or the error found parsing it.
+    pub project: Result<Project, String>,
+}
+
+impl DataFileEntry {
+    /// Create a new data file entry from the project provided.
+    fn from_project(line: Option<usize>, project: Project) -> Self {
+        Self {
+            line,
+            name: Some(project.name.clone()),
+            project: Ok(project),
+        }
+    }
+}
+
+/// Parse the projects available in the data file provided, mapping them into
+/// the registrar's projects. Each entry in the data file is parsed
+/// separately, so that an invalid entry does not prevent the remaining ones
+/// from being processed. An error is only returned when the data file as a
+/// whole cannot be processed.
+pub(crate) fn parse_projects(
+    format: DataFormat,
+    data_url: &str,
+    data: &str,
+) -> Result<Vec<DataFileEntry>> {
+    match format {
+        DataFormat::Clomonitor => parse_clomonitor_projects(data),
+        DataFormat::Json => {
+            let values: Vec<serde_json::Value> = serde_json::from_str(data)?;
+            let entries = values
+                .into_iter()
+                .map(|value| DataFileEntry {
+                    line: None,
+                    name: value["name"].as_str().map(ToString::to_string),
+                    project: serde_json::from_value(value).map_err(|err| err.to_string()),
+                })
+                .collect();
+            Ok(entries)
+        }
+        DataFormat::Landscape => {
+            let landscape: Landscape = serde_yaml::from_str(data)?;
+            let entries = landscape
+                .projects(data_url)
+                .into_iter()
+                .map(|project| DataFileEntry::from_project(None, project))
+                .collect();
+            Ok(entries)
+        }
+    }
+}
+
+/// Parse the projects available in the CLOMonitor style data file provided.
+/// Entries are split using the top level list items, which allows reporting
+/// the line where each entry starts.
+fn parse_clomonitor_projects(data: &str) -> Result<Vec<DataFileEntry>> {
+    // Split data file into entries, tracking the line where each one starts
+    let mut chunks: Vec<(usize, String)> = vec![];
+    for (i, line) in data.lines().enumerate() {
+        if line == "-" || line.starts_with("- ") {
+            chunks.push((i + 1, String::new()));
+        }
+        if let Some((_, chunk)) = chunks.last_mut() {
+            chunk.push_str(line);
+            chunk.push('\n');
+        }
+    }
+
+    // Make sure the data file is a list of entries. When it is not valid YAML
+    // we still try to process each entry separately, as the problem may be
+    // limited to some of them.
+    let values = match serde_yaml::from_str::<Vec<serde_yaml::Value>>(data) {
+        Ok(values) => Some(values),
+        Err(err) if chunks.is_empty() || err.location().is_none() => return Err(err.into()),
+        Err(_) => None,
+    };
+    if let Some(values) = values
+        && values.len() != chunks.len()
+    {
+        // Entries could not be split reliably (i.e. flow style list)
+        let entries = values
+            .into_iter()
+            .map(|value| DataFileEntry {
+                line: None,
+                name: value["name"].as_str().map(ToString::to_string),
+                project: serde_yaml::from_value(value).map_err(|err| err.to_string()),
+            })
+            .collect();
+        return Ok(entries);
+    }
+
+    // Parse each entry separately
+    let entries = chunks
+        .into_iter()
+        .map(|(line, chunk)| {
+            let value = match serde_yaml::from_str::<Vec<serde_yaml::Value>>(&chunk) {
+                Ok(values) => values.into_iter().next().unwrap_or_default(),
+                Err(err) => {
+                    // Adjust the error location, which is relative to the entry
+                    let message = err.to_string();
+                    let (line, message) = match (err.location(), message.rsplit_once(" at line ")) {
+                        (Some(location), Some((message, _))) => {
+                            (line + location.line() - 1, message.to_string())
+                        }
+                        _ => (line, message),
+                    };
+                    return DataFileEntry {
+                        line: Some(line),
+                        name: ENTRY_NAME.captures(&chunk).map(|c| c[1].to_string()),
+                        project: Err(message),
+                    };
+                }
+            };
+            DataFileEntry {
+                line: Some(line),
+                name: value["name"].as_str().map(ToString::to_string),
+                project: serde_yaml::from_value(value).map_err(|err| err.to_string()),
+            }
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// CNCF landscape data file.
+#[derive(Debug, Clone, Deserialize)]
+struct Landscape {
+    landscape: Vec<LandscapeCategory>,
+}
+
+impl Landscape {
+    /// Return the foundation projects available in the landscape. Items that
+    /// are not foundation projects (no maturity set) or that do not have any
+    /// repository are ignored.
+    fn projects(&self, data_url: &str) -> Vec<Project> {
+        self.landscape
+            .iter()
+            .flat_map(|category| {
+                category
+                    .subcategories
+                    .iter()
+                    .map(move |subcategory| (category, subcategory))
+            })
+            .flat_map(|(category, subcategory)| {
+                subcategory
+                    .items
+                    .iter()
+                    .map(move |item| (category, subcategory, item))
+            })
+            .filter_map(|(category, subcategory, item)| {
+                let maturity = item.project.clone()?;
+                let repositories = item.repositories();
+                if repositories.is_empty() {
+                    return None;
+                }
+                let extra = item.extra.clone().unwrap_or_default();
+
+                Some(Project {
+                    name: extra.clomonitor_name.unwrap_or_else(|| slugify(&item.name)),
+                    display_name: Some(item.name.clone()),
+                    description: item
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| item.name.clone()),
+                    logo_url: item
+                        .logo
+                        .as_ref()
+                        .and_then(|logo| landscape_logo_url(data_url, logo)),
+                    logo_dark_url: None,
+                    devstats_url: extra.dev_stats_url,
+                    accepted_at: extra.accepted,
+                    maturity: Some(maturity),
+                    category: Some(category.name.clone()),
+                    subcategory: Some(subcategory.name.clone()),
+                    maintainers_wanted: None,
+                    digest: None,
+                    repositories,
+                    github_orgs: None,
+                    exclude: None,
+                    include: None,
+                    enrichment: None,
+                    enrichment_failed: false,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Landscape category.
+#[derive(Debug, Clone, Deserialize)]
+struct LandscapeCategory {
+    name: String,
+    #[serde(default)]
+    subcategories: Vec<LandscapeSubcategory>,
+}
+
+/// Landscape subcategory.
+#[derive(Debug, Clone, Deserialize)]
+struct LandscapeSubcategory {
+    name: String,
+    #[serde(default)]
+    items: Vec<LandscapeItem>,
+}
+
+/// Landscape item.
+#[derive(Debug, Clone, Deserialize)]
+struct LandscapeItem {
+    name: String,
+    description: Option<String>,
+    logo: Option<String>,
+    project: Option<String>,
+    repo_url: Option<String>,
+    additional_repos: Option<Vec<LandscapeAdditionalRepo>>,
+    extra: Option<LandscapeItemExtra>,
+}
+
+impl LandscapeItem {
+    /// Return the item's repositories.
+    fn repositories(&self) -> Vec<Repository> {
+        self.repo_url
+            .iter()
+            .chain(self.additional_repos.iter().flatten().map(|r| &r.repo_url))
+            .map(|url| url.trim_end_matches('/'))
+            .map(|url| Repository {
+                name: url.rsplit('/').next().unwrap_or(url).to_string(),
+                url: url.to_string(),
+                exclude: None,
+                issues_filter_label: None,
+                include: None,
+                label_overrides: None,
+                docs_only: None,
+                github_node_id: None,
+            })
+            .collect()
+    }
+}
+
+/// Landscape item additional repository.
+#[derive(Debug, Clone, Deserialize)]
+struct LandscapeAdditionalRepo {
+    repo_url: String,
+}
+
+/// Landscape item extra information.
+#[derive(Debug, Clone, Default, Deserialize)]
+struct LandscapeItemExtra {
+    accepted: Option<String>,
+    clomonitor_name: Option<String>,
+    dev_stats_url: Option<String>,
+}
+
+/// Build the url of a landscape logo. Logos are hosted next to the landscape
+/// data file, unless an absolute url is provided.
+fn landscape_logo_url(data_url: &str, logo: &str) -> Option<String> {
+    if logo.starts_with("http://") || logo.starts_with("https://") {
+        return Some(logo.to_string());
+    }
+    if !data_url.starts_with("http://") && !data_url.starts_with("https://") {
+        return None;
+    }
+    let (base_url, _) = data_url.rsplit_once('/')?;
+    Some(format!("{base_url}/{LANDSCAPE_LOGOS_PATH}/{logo}"))
+}
+
+/// Convert the landscape item name provided into a project name.
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTDATA_PATH: &str = "src/testdata";
+
+    #[tokio::test]
+    async fn fetch_local_data_file() {
+        let http_client = reqwest::Client::new();
+        let path = format!("{}/{TESTDATA_PATH}/cncf.yaml", env!("CARGO_MANIFEST_DIR"));
+        let expected = std::fs::read_to_string(&path).unwrap();
+        let cache = DataFileCache::default();
+
+        let data_file = fetch_data_file(&http_client, &path, &cache)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data_file.content, expected);
+        assert!(data_file.cache.digest.is_some());
+
+        let data_file = fetch_data_file(&http_client, &format!("file://{path}"), &cache)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data_file.content, expected);
+
+        // Same digest, not modified
+        let not_modified = fetch_data_file(&http_client, &path, &data_file.cache)
+            .await
+            .unwrap();
+        assert!(not_modified.is_none());
+
+        // Same digest, but cached using a previous data cache version
+        let cache = DataFileCache {
+            version: Some(DATA_CACHE_VERSION - 1),
+            ..data_file.cache
+        };
+        let data_file = fetch_data_file(&http_client, &path, &cache)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data_file.cache.version, Some(DATA_CACHE_VERSION));
+    }
+
+    #[tokio::test]
+    async fn fetch_data_file_invalid_url() {
+        let http_client = reqwest::Client::new();
+        let cache = DataFileCache::default();
+
+        for data_url in [
+            "htps://data.url/cncf.yaml",
+            "ftp://data.url/cncf.yaml",
+            "cncf.yaml",
+        ] {
+            let result = fetch_data_file(&http_client, data_url, &cache).await;
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!(
+                    "invalid data url {data_url} (expected an http(s) or file:// url, or an absolute path)"
+                )
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_remote_data_file_conditionally() {
+        let http_client = reqwest::Client::new();
+        let mut server = mockito::Server::new_async().await;
+        let data_url = format!("{}/cncf.yaml", server.url());
+
+        // First request: no cache information available
+        let req = server
+            .mock("GET", "/cncf.yaml")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"etag1\"")
+            .with_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_body("data")
+            .create_async()
+            .await;
+        let data_file = fetch_data_file(&http_client, &data_url, &DataFileCache::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(data_file.content, "data");
+        assert_eq!(
+            data_file.cache,
+            DataFileCache {
+                etag: Some("\"etag1\"".to_string()),
+                last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+                digest: Some(hex::encode(Sha256::digest("data"))),
+                version: Some(DATA_CACHE_VERSION),
+            }
+        );
+        req.assert_async().await;
+
+        // Second request: cache information sent, not modified
+        let req = server
+            .mock("GET", "/cncf.yaml")
+            .match_header("if-none-match", "\"etag1\"")
+            .match_header("if-modified-since", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .with_status(304)
+            .create_async()
+            .await;
+        let data_file = fetch_data_file(&http_client, &data_url, &data_file.cache)
+            .await
+            .unwrap();
+        assert!(data_file.is_none());
+        req.assert_async().await;
+
+        // Third request: cache information from a previous data cache version
+        // is not sent
+        let req = server
+            .mock("GET", "/cncf.yaml")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .match_header("if-modified-since", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body("data")
+            .create_async()
+            .await;
+        let cache = DataFileCache {
+            etag: Some("\"etag1\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            digest: Some(hex::encode(Sha256::digest("data"))),
+            version: None,
+        };
+        let data_file = fetch_data_file(&http_client, &data_url, &cache)
+            .await
+            .unwrap();
+        assert!(data_file.is_some());
+        req.assert_async().await;
+    }
+
+    #[test]
+    fn parse_json_projects() {
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf.json")).unwrap();
+        let projects = entries_projects(parse_projects(DataFormat::Json, "", &data).unwrap());
+
+        let yaml_data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf.yaml")).unwrap();
+        let yaml_projects =
+            entries_projects(parse_projects(DataFormat::Clomonitor, "", &yaml_data).unwrap());
+        assert_eq!(projects, yaml_projects);
+    }
+
+    #[test]
+    fn parse_landscape_projects() {
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/landscape.yml")).unwrap();
+        let projects = entries_projects(
+            parse_projects(
+                DataFormat::Landscape,
+                "https://raw.githubusercontent.com/cncf/landscape/master/landscape.yml",
+                &data,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            projects,
+            vec![
+                Project {
+                    name: "artifact-hub".to_string(),
+                    display_name: Some("Artifact Hub".to_string()),
+                    description: "Artifact Hub is a web-based application that enables finding, installing, and publishing packages and configurations for CNCF projects".to_string(),
+                    logo_url: Some("https://raw.githubusercontent.com/cncf/landscape/master/hosted_logos/artifacthub.svg".to_string()),
+                    logo_dark_url: None,
+                    devstats_url: Some("https://artifacthub.devstats.cncf.io/".to_string()),
+                    accepted_at: Some("2020-06-23".to_string()),
+                    maturity: Some("incubating".to_string()),
+                    category: Some("App Definition and Development".to_string()),
+                    subcategory: Some("Application Definition & Image Build".to_string()),
+                    maintainers_wanted: None,
+                    digest: None,
+                    repositories: vec![
+                        Repository {
+                            name: "hub".to_string(),
+                            url: "https://github.com/artifacthub/hub".to_string(),
+                            exclude: None,
+                            issues_filter_label: None,
+                            include: None,
+                            label_overrides: None,
+                            docs_only: None,
+                            github_node_id: None,
+                        },
+                        Repository {
+                            name: "helm-plugin".to_string(),
+                            url: "https://github.com/artifacthub/helm-plugin".to_string(),
+                            exclude: None,
+                            issues_filter_label: None,
+                            include: None,
+                            label_overrides: None,
+                            docs_only: None,
+                            github_node_id: None,
+                        },
+                    ],
+                    github_orgs: None,
+                    exclude: None,
+                    include: None,
+                    enrichment: None,
+                    enrichment_failed: false,
+                },
+                Project {
+                    name: "open-policy-agent-opa".to_string(),
+                    display_name: Some("Open Policy Agent (OPA)".to_string()),
+                    description: "Open Policy Agent (OPA)".to_string(),
+                    logo_url: None,
+                    logo_dark_url: None,
+                    devstats_url: None,
+                    accepted_at: None,
+                    maturity: Some("graduated".to_string()),
+                    category: Some("Provisioning".to_string()),
+                    subcategory: Some("Security & Compliance".to_string()),
+                    maintainers_wanted: None,
+                    digest: None,
+                    repositories: vec![Repository {
+                        name: "opa".to_string(),
+                        url: "https://github.com/open-policy-agent/opa".to_string(),
+                        exclude: None,
+                        issues_filter_label: None,
+                        include: None,
+                        label_overrides: None,
+                        docs_only: None,
+                        github_node_id: None,
+                    }],
+                    github_orgs: None,
+                    exclude: None,
+                    include: None,
+                    enrichment: None,
+                    enrichment_failed: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_clomonitor_projects_invalid_entries() {
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf-invalid.yaml")).unwrap();
+        let entries = parse_projects(DataFormat::Clomonitor, "", &data).unwrap();
+
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].line, Some(1));
+        assert_eq!(entries[0].name.as_deref(), Some("project1"));
+        assert!(entries[0].project.is_ok());
+        assert_eq!(entries[1].line, Some(8));
+        assert_eq!(entries[1].name.as_deref(), Some("project2"));
+        assert_eq!(
+            entries[1].project.as_ref().unwrap_err(),
+            "missing field `description`"
+        );
+        assert_eq!(entries[2].line, Some(13));
+        assert_eq!(entries[2].name.as_deref(), Some("project3"));
+        assert!(entries[2].project.is_ok());
+    }
+
+    #[test]
+    fn parse_clomonitor_projects_invalid_yaml_entry() {
+        let data = r#"- name: project1
+  description: Project 1 description
+  repositories: []
+- name: "project2"
+  description: [Project 2 description
+  repositories: []
+"#;
+        let entries = parse_projects(DataFormat::Clomonitor, "", data).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].project.is_ok());
+        assert_eq!(entries[1].line, Some(6));
+        assert_eq!(entries[1].name.as_deref(), Some("project2"));
+        assert!(entries[1].project.is_err());
+    }
+
+    fn entries_projects(entries: Vec<DataFileEntry>) -> Vec<Project> {
+        entries
+            .into_iter()
+            .map(|entry| entry.project.unwrap())
+            .collect()
+    }
+}
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -7,7 +10,10 @@ use deadpool_postgres::Pool;
 use mockall::automock;
 use tokio_postgres::types::Json;
 
-use crate::registrar::{Foundation, Project};
+use crate::{
+    datasource::DataFileCache,
+    registrar::{Foundation, FoundationConfig, FoundationSync, ProjectFailure, Repository, Run},
+};
 
 /// Type alias to represent a DB trait object.
 pub(crate) type DynDB = Arc<dyn DB + Send + Sync>;
@@ -16,7 +22,7 @@ pub(crate) type DynDB = Arc<dyn DB + Send + Sync>;
 #[async_trait]
 #[cfg_attr(test, automock)]
 pub(crate) trait DB {
-    /// Get foundations registered in the database.
+    /// Get enabled foundations registered in the database.
     async fn foundations(&self) -> Result<Vec<Foundation>>;
 
     /// Get projects for the foundation provided.
@@ -25,11 +31,39 @@ pub(crate) trait DB {
         foundation_id: &str,
     ) -> Result<HashMap<String, Option<String>>>;
 
-    /// Register project provided in the database.
-    async fn register_project(&self, foundation_id: &str, project: &Project) -> Result<()>;
+    /// Get the repositories of the foundation provided, grouped by project.
+    async fn foundation_repositories(
+        &self,
+        foundation_id: &str,
+    ) -> Result<HashMap<String, Vec<Repository>>>;
+
+    /// Delete permanently the projects of the foundation provided that were
+    /// unregistered longer than the grace period provided ago.
+    async fn purge_unregistered_projects(
+        &self,
+        foundation_id: &str,
+        grace_period_days: i32,
+    ) -> Result<i32>;
+
+    /// Reconcile the foundations registered in the database with the ones
+    /// provided, disabling the foundations no longer available.
+    async fn reconcile_foundations(&self, foundations: &[FoundationConfig]) -> Result<()>;
+
+    /// Register run provided in the database.
+    async fn register_run(&self, foundation_id: &str, run: &Run) -> Result<()>;
+
+    /// Apply the foundation changes provided in the database in a single
+    /// transaction. Projects that fail to be registered or unregistered are
+    /// skipped and returned, and the transaction is only committed when the
+    /// number of failures does not exceed the maximum allowed.
+    async fn sync_foundation(&self, sync: &FoundationSync) -> Result<Vec<ProjectFailure>>;
 
-    /// Unregister project provided from the database.
-    async fn unregister_project(&self, foundation_id: &str, project_name: &str) -> Result<()>;
+    /// Update the foundation's data file cache information.
+    async fn update_foundation_data_cache(
+        &self,
+        foundation_id: &str,
+        data_cache: &DataFileCache,
+    ) -> Result<()>;
 }
 
 /// DB implementation backed by PostgreSQL.
@@ -49,12 +83,35 @@ impl DB for PgDB {
     async fn foundations(&self) -> Result<Vec<Foundation>> {
         let db = self.pool.get().await?;
         let foundations = db
-            .query("select foundation_id, data_url from foundation", &[])
+            .query(
+                "
+                select
+                    foundation_id,
+                    data_url,
+                    data_format,
+                    data_etag,
+                    data_last_modified,
+                    data_digest,
+                    data_cache_version,
+                    opt_in
+                from foundation
+                where enabled = true
+                ",
+                &[],
+            )
             .await?
             .iter()
             .map(|row| Foundation {
                 foundation_id: row.get("foundation_id"),
                 data_url: row.get("data_url"),
+                data_format: row.get("data_format"),
+                data_cache: DataFileCache {
+                    etag: row.get("data_etag"),
+                    last_modified: row.get("data_last_modified"),
+                    digest: row.get("data_digest"),
+                    version: row.get("data_cache_version"),
+                },
+                opt_in: row.get("opt_in"),
             })
             .collect();
         Ok(foundations)
@@ -67,7 +124,12 @@ impl DB for PgDB {
         let db = self.pool.get().await?;
         let projects = db
             .query(
-                "select name, digest from project where foundation_id = $1::text",
+                "
+                select name, digest
+                from project
+                where foundation_id = $1::text
+                and deleted_at is null
+                ",
                 &[&foundation_id],
             )
             .await?
@@ -77,23 +139,816 @@ impl DB for PgDB {
         Ok(projects)
     }
 
-    async fn register_project(&self, foundation_id: &str, project: &Project) -> Result<()> {
+    async fn foundation_repositories(
+        &self,
+        foundation_id: &str,
+    ) -> Result<HashMap<String, Vec<Repository>>> {
+        let db = self.pool.get().await?;
+        let mut repositories: HashMap<String, Vec<Repository>> = HashMap::new();
+        for row in db
+            .query(
+                "
+                select
+                    p.name as project_name,
+                    r.name,
+                    r.url,
+                    r.issues_filter_label,
+                    r.label_overrides,
+                    r.docs_only,
+                    r.github_node_id
+                from repository r
+                join project p using (project_id)
+                where p.foundation_id = $1::text
+                and p.deleted_at is null
+                order by r.url asc
+                ",
+                &[&foundation_id],
+            )
+            .await?
+        {
+            repositories
+                .entry(row.get("project_name"))
+                .or_default()
+                .push(Repository {
+                    name: row.get("name"),
+                    url: row.get("url"),
+                    exclude: None,
+                    issues_filter_label: row.get("issues_filter_label"),
+                    include: None,
+                    label_overrides: row
+                        .get::<_, Option<Json<BTreeMap<String, String>>>>("label_overrides")
+                        .map(|label_overrides| label_overrides.0),
+                    docs_only: Some(row.get("docs_only")),
+                    github_node_id: row.get("github_node_id"),
+                });
+        }
+        Ok(repositories)
+    }
+
+    async fn purge_unregistered_projects(
+        &self,
+        foundation_id: &str,
+        grace_period_days: i32,
+    ) -> Result<i32> {
+        let db = self.pool.get().await?;
+        let row = db
+            .query_one(
+                "select purge_unregistered_projects($1::text, $2::integer)",
+                &[&foundation_id, &grace_period_days],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    async fn reconcile_foundations(&self, foundations: &[FoundationConfig]) -> Result<()> {
+        let db = self.pool.get().await?;
+        db.execute(
+            "select reconcile_foundations($1::jsonb)",
+            &[&Json(foundations)],
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn register_run(&self, foundation_id: &str, run: &Run) -> Result<()> {
         let db = self.pool.get().await?;
         db.execute(
-            "select register_project($1::text, $2::jsonb)",
-            &[&foundation_id, &Json(project)],
+            "select register_run('registrar', $1::text, $2::timestamptz, $3::integer, $4::text[], $5::text[])",
+            &[
+                &foundation_id,
+                &run.started_at,
+                &run.processed,
+                &run.errors,
+                &run.failures,
+            ],
         )
         .await?;
         Ok(())
     }
 
-    async fn unregister_project(&self, foundation_id: &str, project_name: &str) -> Result<()> {
+    async fn sync_foundation(&self, sync: &FoundationSync) -> Result<Vec<ProjectFailure>> {
+        let mut db = self.pool.get().await?;
+        let mut tx = db.transaction().await?;
+        let foundation_id = &sync.foundation_id;
+        let mut failures = vec![];
+
+        // Register projects (each one in its own savepoint, so that a failure
+        // does not affect the rest of the changes)
+        for project in &sync.projects_to_register {
+            let sp = tx.savepoint("register_project").await?;
+            match sp
+                .execute(
+                    "select register_project($1::text, $2::jsonb)",
+                    &[&foundation_id, &Json(project)],
+                )
+                .await
+            {
+                Ok(_) => sp.commit().await?,
+                Err(err) => {
+                    sp.rollback().await?;
+                    failures.push(ProjectFailure {
+                        project: project.name.clone(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        // Unregister projects
+        for project_name in &sync.projects_to_unregister {
+            let sp = tx.savepoint("unregister_project").await?;
+            match sp
+                .execute(
+                    "select unregister_project($1::text, $2::text)",
+                    &[&foundation_id, &project_name],
+                )
+                .await
+            {
+                Ok(_) => sp.commit().await?,
+                Err(err) => {
+                    sp.rollback().await?;
+                    failures.push(ProjectFailure {
+                        project: project_name.clone(),
+                        error: err.to_string(),
+                    });
+                }
+            }
+        }
+
+        // Nothing is applied if there were too many failures
+        if failures.len() <= sync.max_failures {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(failures)
+    }
+
+    async fn update_foundation_data_cache(
+        &self,
+        foundation_id: &str,
+        data_cache: &DataFileCache,
+    ) -> Result<()> {
         let db = self.pool.get().await?;
         db.execute(
-            "select unregister_project($1::text, $2::text)",
-            &[&foundation_id, &project_name],
+            "
+            update foundation set
+                data_etag = $2::text,
+                data_last_modified = $3::text,
+                data_digest = $4::text,
+                data_cache_version = $5::integer
+            where foundation_id = $1::text
+            ",
+            &[
+                &foundation_id,
+                &data_cache.etag,
+                &data_cache.last_modified,
+                &data_cache.digest,
+                &data_cache.version,
+            ],
         )
         .await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{datasource::DataFormat, github::RepositoryMetadata, registrar::Project};
+    use clotributor_testutils::FOUNDATION;
+    use serde_json::Value;
+    use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn foundations() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+
+        let foundations = db.foundations().await.unwrap();
+        assert_eq!(foundations.len(), 1);
+        assert_eq!(foundations[0].foundation_id, FOUNDATION);
+        assert_eq!(foundations[0].data_url, "https://data.url");
+        assert_eq!(foundations[0].data_format, DataFormat::Clomonitor);
+        assert_eq!(foundations[0].data_cache, DataFileCache::default());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn update_foundation_data_cache() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let data_cache = DataFileCache {
+            etag: Some("etag".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            digest: Some("digest".to_string()),
+            version: Some(1),
+        };
+
+        db.update_foundation_data_cache(FOUNDATION, &data_cache)
+            .await
+            .unwrap();
+        let foundations = db.foundations().await.unwrap();
+        assert_eq!(foundations[0].data_cache, data_cache);
+
+        // Cache is reset when the data file location changes
+        db.reconcile_foundations(&[FoundationConfig {
+            foundation_id: FOUNDATION.to_string(),
+            display_name: "CNCF".to_string(),
+            data_url: "https://new.data.url".to_string(),
+            data_format: DataFormat::Clomonitor,
+            enabled: true,
+            opt_in: false,
+        }])
+        .await
+        .unwrap();
+        let foundations = db.foundations().await.unwrap();
+        assert_eq!(foundations[0].data_cache, DataFileCache::default());
+
+        // Cache is reset when the opt-in mode changes
+        db.update_foundation_data_cache(FOUNDATION, &data_cache)
+            .await
+            .unwrap();
+        db.reconcile_foundations(&[FoundationConfig {
+            foundation_id: FOUNDATION.to_string(),
+            display_name: "CNCF".to_string(),
+            data_url: "https://new.data.url".to_string(),
+            data_format: DataFormat::Clomonitor,
+            enabled: true,
+            opt_in: true,
+        }])
+        .await
+        .unwrap();
+        let foundations = db.foundations().await.unwrap();
+        assert!(foundations[0].opt_in);
+        assert_eq!(foundations[0].data_cache, DataFileCache::default());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn reconcile_foundations() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+
+        db.reconcile_foundations(&[FoundationConfig {
+            foundation_id: "lfai".to_string(),
+            display_name: "LF AI & Data".to_string(),
+            data_url: "https://lfai.data.url".to_string(),
+            data_format: DataFormat::Landscape,
+            enabled: true,
+            opt_in: false,
+        }])
+        .await
+        .unwrap();
+
+        // Foundations no longer declared must be disabled
+        let foundations = db.foundations().await.unwrap();
+        assert_eq!(foundations.len(), 1);
+        assert_eq!(foundations[0].foundation_id, "lfai");
+        assert_eq!(foundations[0].data_url, "https://lfai.data.url");
+        assert_eq!(foundations[0].data_format, DataFormat::Landscape);
+
+        // Existing foundations must be updated (and enabled again)
+        db.reconcile_foundations(&[FoundationConfig {
+            foundation_id: FOUNDATION.to_string(),
+            display_name: "Cloud Native Computing Foundation".to_string(),
+            data_url: "https://new.data.url".to_string(),
+            data_format: DataFormat::Clomonitor,
+            enabled: true,
+            opt_in: false,
+        }])
+        .await
+        .unwrap();
+        let foundations = db.foundations().await.unwrap();
+        assert_eq!(foundations.len(), 1);
+        assert_eq!(foundations[0].foundation_id, FOUNDATION);
+        assert_eq!(foundations[0].data_url, "https://new.data.url");
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "select display_name from foundation where foundation_id = $1::text",
+                &[&FOUNDATION],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<_, String>("display_name"),
+            "Cloud Native Computing Foundation"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_project() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let project = sample_project();
+
+        register(&db, &project).await;
+
+        // All project fields must round-trip
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "
+                select
+                    name,
+                    display_name,
+                    description,
+                    logo_url,
+                    logo_dark_url,
+                    devstats_url,
+                    accepted_at::text,
+                    maturity,
+                    category,
+                    subcategory,
+                    maintainers_wanted,
+                    digest
+                from project
+                where foundation_id = $1::text
+                ",
+                &[&FOUNDATION],
+            )
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>("name"), project.name);
+        assert_eq!(
+            row.get::<_, Option<String>>("display_name"),
+            project.display_name
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("description"),
+            Some(project.description.clone())
+        );
+        assert_eq!(row.get::<_, Option<String>>("logo_url"), project.logo_url);
+        assert_eq!(
+            row.get::<_, Option<String>>("logo_dark_url"),
+            project.logo_dark_url
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("devstats_url"),
+            project.devstats_url
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("accepted_at"),
+            project.accepted_at
+        );
+        assert_eq!(row.get::<_, Option<String>>("maturity"), project.maturity);
+        assert_eq!(row.get::<_, Option<String>>("category"), project.category);
+        assert_eq!(
+            row.get::<_, Option<String>>("subcategory"),
+            project.subcategory
+        );
+        assert_eq!(
+            row.get::<_, Option<Json<Value>>>("maintainers_wanted")
+                .map(|v| v.0),
+            Some(serde_json::to_value(&project.maintainers_wanted).unwrap())
+        );
+        assert_eq!(row.get::<_, Option<String>>("digest"), project.digest);
+
+        // All repositories fields must round-trip
+        assert_eq!(
+            registered_repositories(&db).await,
+            vec![
+                (
+                    "repo1".to_string(),
+                    "https://github.com/org/repo1".to_string(),
+                    Some("label".to_string())
+                ),
+                (
+                    "repo2".to_string(),
+                    "https://github.com/org/repo2".to_string(),
+                    None
+                ),
+            ]
+        );
+        let row = client
+            .query_one(
+                "select label_overrides, docs_only from repository where name = 'repo2'",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<_, Option<Json<Value>>>("label_overrides")
+                .map(|v| v.0),
+            Some(serde_json::json!({"beginner": "good first issue"}))
+        );
+        assert!(row.get::<_, bool>("docs_only"));
+
+        // Project must be returned as part of the foundation's projects
+        let projects = db.foundation_projects(FOUNDATION).await.unwrap();
+        assert_eq!(
+            projects,
+            HashMap::from([(project.name.clone(), project.digest.clone())])
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn foundation_repositories() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let project = sample_project();
+        register(&db, &project).await;
+
+        let repositories = db.foundation_repositories(FOUNDATION).await.unwrap();
+        assert_eq!(repositories.len(), 1);
+        assert_eq!(
+            repositories[&project.name],
+            vec![
+                Repository {
+                    docs_only: Some(false),
+                    ..project.repositories[0].clone()
+                },
+                project.repositories[1].clone(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_project_update_existing() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let mut project = sample_project();
+        register(&db, &project).await;
+
+        project.repositories.remove(0);
+        project.repositories[0].issues_filter_label = Some("new label".to_string());
+        project.digest = Some("new digest".to_string());
+        register(&db, &project).await;
+
+        assert_eq!(
+            registered_repositories(&db).await,
+            vec![(
+                "repo2".to_string(),
+                "https://github.com/org/repo2".to_string(),
+                Some("new label".to_string())
+            )]
+        );
+        let projects = db.foundation_projects(FOUNDATION).await.unwrap();
+        assert_eq!(
+            projects,
+            HashMap::from([(project.name.clone(), project.digest.clone())])
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_project_with_enrichment() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let mut project = sample_project();
+        project.enrichment = Some(RepositoryMetadata {
+            description: Some("Repository description".to_string()),
+            homepage_url: Some("https://homepage.url".to_string()),
+            social_preview_url: None,
+            contributing_url: Some("https://contributing.url".to_string()),
+        });
+        register(&db, &project).await;
+
+        // Enrichment data is stored separately from the data file values
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "
+                select
+                    description,
+                    enriched_description,
+                    enriched_homepage_url,
+                    enriched_social_preview_url,
+                    enriched_contributing_url
+                from project
+                ",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>("description"), project.description);
+        assert_eq!(
+            row.get::<_, Option<String>>("enriched_description"),
+            Some("Repository description".to_string())
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("enriched_homepage_url"),
+            Some("https://homepage.url".to_string())
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("enriched_social_preview_url"),
+            None
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("enriched_contributing_url"),
+            Some("https://contributing.url".to_string())
+        );
+
+        // The enrichment data stored is kept when the project could not be
+        // enriched this time
+        project.enrichment = None;
+        project.enrichment_failed = true;
+        register(&db, &project).await;
+        let enriched_description: Option<String> = client
+            .query_one("select enriched_description from project", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(
+            enriched_description,
+            Some("Repository description".to_string())
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_project_category_changed() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let mut project = sample_project();
+        register(&db, &project).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(
+                "
+                insert into issue (issue_id, title, url, number, labels, digest, tsdoc, repository_id)
+                select 1, 'issue1', 'https://issue1.url', 1, '{}', 'digest', ''::tsvector, repository_id
+                from repository
+                where name = 'repo1';
+                ",
+            )
+            .await
+            .unwrap();
+        let issue_digest = async || -> Option<String> {
+            client
+                .query_one("select digest from issue where issue_id = 1", &[])
+                .await
+                .unwrap()
+                .get("digest")
+        };
+
+        // Issues digest is kept when the category does not change
+        project.digest = Some("new digest".to_string());
+        register(&db, &project).await;
+        assert_eq!(issue_digest().await, Some("digest".to_string()));
+
+        // Issues digest is reset when the category changes
+        project.subcategory = Some("new subcategory".to_string());
+        register(&db, &project).await;
+        assert_eq!(issue_digest().await, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_project_renamed_repository() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let mut project = sample_project();
+        project.repositories[0].github_node_id = Some("R_1".to_string());
+        register(&db, &project).await;
+        let repositories = db.foundation_repositories(FOUNDATION).await.unwrap();
+        let node_id = |repositories: &HashMap<String, Vec<Repository>>, url: &str| {
+            repositories[&project.name]
+                .iter()
+                .find(|r| r.url == url)
+                .map(|r| r.github_node_id.clone())
+        };
+        assert_eq!(
+            node_id(&repositories, "https://github.com/org/repo1"),
+            Some(Some("R_1".to_string()))
+        );
+
+        // The renamed repository must keep its row (and issues)
+        let client = db.pool.get().await.unwrap();
+        let get_id = async |url: &str| -> Option<String> {
+            client
+                .query_opt(
+                    "select repository_id::text from repository where url = $1",
+                    &[&url],
+                )
+                .await
+                .unwrap()
+                .map(|row| row.get(0))
+        };
+        let id_before = get_id("https://github.com/org/repo1").await;
+        project.repositories[0].name = "renamed".to_string();
+        project.repositories[0].url = "https://github.com/org/renamed".to_string();
+        register(&db, &project).await;
+        assert!(get_id("https://github.com/org/repo1").await.is_none());
+        assert_eq!(get_id("https://github.com/org/renamed").await, id_before);
+
+        // The node id is kept when the data file does not provide it
+        project.repositories[0].github_node_id = None;
+        register(&db, &project).await;
+        let repositories = db.foundation_repositories(FOUNDATION).await.unwrap();
+        assert_eq!(
+            node_id(&repositories, "https://github.com/org/renamed"),
+            Some(Some("R_1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn unregister_project() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let project = sample_project();
+        register(&db, &project).await;
+
+        unregister(&db, &project.name).await;
+
+        // Project is soft deleted, its repositories are kept until purged
+        assert!(db.foundation_projects(FOUNDATION).await.unwrap().is_empty());
+        assert_eq!(registered_repositories(&db).await.len(), 2);
+
+        // Projects within the grace period are not purged
+        assert_eq!(
+            db.purge_unregistered_projects(FOUNDATION, 7).await.unwrap(),
+            0
+        );
+        assert_eq!(registered_repositories(&db).await.len(), 2);
+
+        // Registering the project again restores it
+        register(&db, &project).await;
+        assert_eq!(db.foundation_projects(FOUNDATION).await.unwrap().len(), 1);
+
+        // Projects unregistered longer than the grace period ago are purged
+        unregister(&db, &project.name).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .execute(
+                "update project set deleted_at = current_timestamp - '8 days'::interval",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            db.purge_unregistered_projects(FOUNDATION, 7).await.unwrap(),
+            1
+        );
+        assert!(registered_repositories(&db).await.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn sync_foundation_with_failures() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let project = sample_project();
+        let invalid_project = Project {
+            name: "invalid".to_string(),
+            description: String::new(),
+            ..sample_project()
+        };
+        let mut sync = FoundationSync {
+            foundation_id: FOUNDATION.to_string(),
+            projects_to_register: vec![project.clone(), invalid_project],
+            projects_to_unregister: vec![],
+            max_failures: 0,
+        };
+
+        // Nothing is applied when the maximum number of failures is exceeded
+        let failures = db.sync_foundation(&sync).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].project, "invalid");
+        assert!(db.foundation_projects(FOUNDATION).await.unwrap().is_empty());
+
+        // The rest of the changes are applied otherwise
+        sync.max_failures = 1;
+        let failures = db.sync_foundation(&sync).await.unwrap();
+        assert_eq!(failures.len(), 1);
+        let projects = db.foundation_projects(FOUNDATION).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert!(projects.contains_key(&project.name));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_run() {
+        let db = setup_test_db().await;
+        seed_foundation(&db).await;
+        let run = Run {
+            started_at: OffsetDateTime::parse("2023-01-02T03:04:05.123456Z", &Rfc3339).unwrap(),
+            processed: 2,
+            errors: vec![],
+            failures: vec!["project: error".to_string()],
+        };
+
+        db.register_run(FOUNDATION, &run).await.unwrap();
+
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "select kind, status, processed, errors, failures, started_at, foundation_id from run",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>("kind"), "registrar");
+        assert_eq!(row.get::<_, String>("status"), "succeeded");
+        assert_eq!(row.get::<_, i32>("processed"), run.processed);
+        assert_eq!(row.get::<_, Option<Vec<String>>>("errors"), None);
+        assert_eq!(
+            row.get::<_, Option<Vec<String>>>("failures"),
+            Some(run.failures)
+        );
+        assert_eq!(row.get::<_, OffsetDateTime>("started_at"), run.started_at);
+        assert_eq!(
+            row.get::<_, Option<String>>("foundation_id"),
+            Some(FOUNDATION.to_string())
+        );
+    }
+
+    /// Setup a new database for a test (see clotributor_testutils).
+    async fn setup_test_db() -> PgDB {
+        PgDB::new(clotributor_testutils::setup_test_db().await)
+    }
+
+    /// Seed the database with the sample foundation.
+    async fn seed_foundation(db: &PgDB) {
+        clotributor_testutils::seed_foundation(&db.pool).await;
+    }
+
+    /// Get the name, url and issues filter label of the repositories
+    /// registered, sorted by name.
+    async fn registered_repositories(db: &PgDB) -> Vec<(String, String, Option<String>)> {
+        let client = db.pool.get().await.unwrap();
+        client
+            .query(
+                "select name, url, issues_filter_label from repository order by name",
+                &[],
+            )
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| {
+                (
+                    row.get("name"),
+                    row.get("url"),
+                    row.get("issues_filter_label"),
+                )
+            })
+            .collect()
+    }
+
+    async fn register(db: &PgDB, project: &Project) {
+        let failures = db
+            .sync_foundation(&FoundationSync {
+                foundation_id: FOUNDATION.to_string(),
+                projects_to_register: vec![project.clone()],
+                projects_to_unregister: vec![],
+                max_failures: 0,
+            })
+            .await
+            .unwrap();
+        assert!(failures.is_empty());
+    }
+
+    async fn unregister(db: &PgDB, project_name: &str) {
+        let failures = db
+            .sync_foundation(&FoundationSync {
+                foundation_id: FOUNDATION.to_string(),
+                projects_to_register: vec![],
+                projects_to_unregister: vec![project_name.to_string()],
+                max_failures: 0,
+            })
+            .await
+            .unwrap();
+        assert!(failures.is_empty());
+    }
+
+    fn sample_project() -> Project {
+        serde_yaml::from_str(
+            r"
+            name: project
+            display_name: Project
+            description: Project description
+            logo_url: https://logo.url
+            logo_dark_url: https://logo.dark.url
+            devstats_url: https://devstats.url
+            accepted_at: 2023-01-02
+            maturity: sandbox
+            category: app definition
+            subcategory: image build
+            maintainers_wanted:
+              enabled: true
+              links:
+                - title: Contributing
+                  url: https://contributing.url
+              contacts:
+                - github_handle: user1
+            digest: digest
+            repositories:
+              - name: repo1
+                url: https://github.com/org/repo1
+                issues_filter_label: label
+              - name: repo2
+                url: https://github.com/org/repo2
+                label_overrides:
+                  beginner: good first issue
+                docs_only: true
+            ",
+        )
+        .unwrap()
+    }
+}
@@ -0,0 +1,479 @@
+use std::sync::{Arc, LazyLock};
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+use regex::Regex;
+use reqwest::{StatusCode, header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// GitHub REST API URL.
+pub(crate) const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Number of repositories requested per page when listing an organization's
+/// repositories (maximum allowed by the GitHub API).
+const ORG_REPOSITORIES_PER_PAGE: usize = 100;
+
+/// GraphQL query used to get a repository's metadata.
+const REPOSITORY_METADATA_QUERY: &str = "
+query($owner: String!, $name: String!) {
+    repository(owner: $owner, name: $name) {
+        description
+        homepageUrl
+        openGraphImageUrl
+        usesCustomOpenGraphImage
+        contributingGuidelines {
+            url
+        }
+    }
+}
+";
+
+static GITHUB_REPO_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("^https://github.com/(?P<owner>[^/]+)/(?P<repo>[^/]+?)/?$")
+        .expect("exprs in GITHUB_REPO_URL to be valid")
+});
+
+/// Type alias to represent a GH trait object.
+pub(crate) type DynGH = Arc<dyn GH + Send + Sync>;
+
+/// Trait that defines some operations a GH implementation must support.
+#[async_trait]
+#[cfg_attr(test, automock)]
+pub(crate) trait GH {
+    /// Get all public repositories owned by the organization provided.
+    async fn org_repositories(&self, org: &str) -> Result<Vec<OrgRepository>>;
+
+    /// Get the GitHub node id of the repository located at the url provided.
+    /// Renamed repositories are resolved as well, as GitHub redirects their
+    /// previous urls. None is returned if the repository does not exist.
+    async fn repository_node_id(&self, repository_url: &str) -> Result<Option<String>>;
+
+    /// Get some metadata of the repository located at the url provided, used
+    /// to enrich the project it belongs to. None is returned if the repository
+    /// does not exist.
+    async fn repository_metadata(&self, repository_url: &str)
+    -> Result<Option<RepositoryMetadata>>;
+}
+
+/// GH implementation backed by the GitHub REST API.
+pub(crate) struct GHApi {
+    http_client: reqwest::Client,
+    api_url: String,
+}
+
+impl GHApi {
+    /// Create a new GHApi instance.
+    pub(crate) fn new(api_url: &str, token: Option<&str>) -> Result<Self> {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            header::HeaderValue::from_static("application/vnd.github+json"),
+        );
+        if let Some(token) = token {
+            headers.insert(
+                header::AUTHORIZATION,
+                header::HeaderValue::from_str(&format!("Bearer {token}"))
+                    .context("invalid github token")?,
+            );
+        }
+        let http_client = reqwest::Client::builder()
+            .user_agent("clotributor")
+            .default_headers(headers)
+            .build()?;
+
+        Ok(Self {
+            http_client,
+            api_url: api_url.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl GH for GHApi {
+    async fn org_repositories(&self, org: &str) -> Result<Vec<OrgRepository>> {
+        let mut repositories = vec![];
+        let mut page = 1;
+        loop {
+            let url = format!(
+                "{}/orgs/{org}/repos?type=public&sort=full_name&per_page={ORG_REPOSITORIES_PER_PAGE}&page={page}",
+                self.api_url
+            );
+            let resp = self.http_client.get(url).send().await?;
+            if resp.status() != StatusCode::OK {
+                bail!(
+                    "unexpected status code listing {org} repositories: {}",
+                    resp.status()
+                );
+            }
+            let page_repositories: Vec<OrgRepository> = resp.json().await?;
+            let last_page = page_repositories.len() < ORG_REPOSITORIES_PER_PAGE;
+
+            // Private and internal repositories must never be registered, as
+            // their details would be published (they are only returned when
+            // the token's account is a member of the organization)
+            repositories.extend(page_repositories.into_iter().filter(|r| !r.private));
+            if last_page {
+                break;
+            }
+            page += 1;
+        }
+        Ok(repositories)
+    }
+
+    async fn repository_node_id(&self, repository_url: &str) -> Result<Option<String>> {
+        let Some(c) = GITHUB_REPO_URL.captures(repository_url) else {
+            bail!("invalid repository url: {repository_url}");
+        };
+        let url = format!("{}/repos/{}/{}", self.api_url, &c["owner"], &c["repo"]);
+        let resp = self.http_client.get(url).send().await?;
+        match resp.status() {
+            StatusCode::OK => {
+                let repository: GHRepository = resp.json().await?;
+                Ok(Some(repository.node_id))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!("unexpected status code getting repository {repository_url}: {status}"),
+        }
+    }
+
+    async fn repository_metadata(
+        &self,
+        repository_url: &str,
+    ) -> Result<Option<RepositoryMetadata>> {
+        let Some(c) = GITHUB_REPO_URL.captures(repository_url) else {
+            bail!("invalid repository url: {repository_url}");
+        };
+        let body = json!({
+            "query": REPOSITORY_METADATA_QUERY,
+            "variables": {
+                "owner": &c["owner"],
+                "name": &c["repo"],
+            },
+        });
+        let url = format!("{}/graphql", self.api_url);
+        let resp = self.http_client.post(url).json(&body).send().await?;
+        if resp.status() != StatusCode::OK {
+            bail!(
+                "unexpected status code getting repository {repository_url} metadata: {}",
+                resp.status()
+            );
+        }
+        let resp: RepositoryMetadataResponse = resp.json().await?;
+
+        // GitHub replies with a 200 status code when the query fails, so any
+        // error other than the repository not being found must be reported
+        let errors = resp.errors.unwrap_or_default();
+        if let Some(err) = errors.iter().find(|err| !err.is_not_found()) {
+            bail!(
+                "error getting repository {repository_url} metadata: {}",
+                err.message.as_deref().unwrap_or("unknown error")
+            );
+        }
+        let Some(data) = resp.data else {
+            bail!("no data returned getting repository {repository_url} metadata");
+        };
+        Ok(data.repository.map(RepositoryMetadata::from))
+    }
+}
+
+/// Represents some information about a GitHub repository.
+#[derive(Debug, Clone, Deserialize)]
+struct GHRepository {
+    node_id: String,
+}
+
+/// GraphQL response returned by the repository metadata query.
+#[derive(Debug, Clone, Deserialize)]
+struct RepositoryMetadataResponse {
+    data: Option<RepositoryMetadataData>,
+    errors: Option<Vec<GHGraphQLError>>,
+}
+
+/// Error returned by the GitHub GraphQL API.
+#[derive(Debug, Clone, Deserialize)]
+struct GHGraphQLError {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    message: Option<String>,
+}
+
+impl GHGraphQLError {
+    /// Check if the error is due to the resource requested not being found.
+    fn is_not_found(&self) -> bool {
+        self.kind.as_deref() == Some("NOT_FOUND")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RepositoryMetadataData {
+    repository: Option<GHRepositoryMetadata>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GHRepositoryMetadata {
+    description: Option<String>,
+    homepage_url: Option<String>,
+    open_graph_image_url: String,
+    uses_custom_open_graph_image: bool,
+    contributing_guidelines: Option<GHContributingGuidelines>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GHContributingGuidelines {
+    url: Option<String>,
+}
+
+/// Metadata of a GitHub repository used to enrich a project.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RepositoryMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub social_preview_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contributing_url: Option<String>,
+}
+
+impl From<GHRepositoryMetadata> for RepositoryMetadata {
+    fn from(repository: GHRepositoryMetadata) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+        Self {
+            description: non_empty(repository.description),
+            homepage_url: non_empty(repository.homepage_url),
+            // The default social preview is just a generated card with the
+            // repository's name, so it is only used when it's a custom one
+            social_preview_url: repository
+                .uses_custom_open_graph_image
+                .then_some(repository.open_graph_image_url),
+            contributing_url: non_empty(repository.contributing_guidelines.and_then(|cg| cg.url)),
+        }
+    }
+}
+
+/// Represents a repository owned by a GitHub organization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OrgRepository {
+    pub name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub private: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn org_repositories_paginated() {
+        let mut server = mockito::Server::new_async().await;
+        let page1: Vec<OrgRepository> = (0..ORG_REPOSITORIES_PER_PAGE)
+            .map(|i| OrgRepository {
+                name: format!("repo{i}"),
+                html_url: format!("https://github.com/org/repo{i}"),
+                archived: false,
+                fork: false,
+                private: false,
+            })
+            .collect();
+        let page2 = vec![OrgRepository {
+            name: "last".to_string(),
+            html_url: "https://github.com/org/last".to_string(),
+            archived: true,
+            fork: false,
+            private: false,
+        }];
+        let page1_req = server
+            .mock("GET", "/orgs/org/repos")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("type".into(), "public".into()),
+                mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+            ]))
+            .match_header("authorization", "Bearer token")
+            .with_status(200)
+            .with_body(serde_json::to_string(&page1).unwrap())
+            .create_async()
+            .await;
+        let page2_req = server
+            .mock("GET", "/orgs/org/repos")
+            .match_query(mockito::Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_status(200)
+            .with_body(serde_json::to_string(&page2).unwrap())
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), Some("token")).unwrap();
+        let repositories = gh.org_repositories("org").await.unwrap();
+        assert_eq!(repositories.len(), ORG_REPOSITORIES_PER_PAGE + 1);
+        assert_eq!(repositories.last(), page2.first());
+        page1_req.assert_async().await;
+        page2_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn repository_node_id_found_and_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let found_req = server
+            .mock("GET", "/repos/org/repo1")
+            .with_status(200)
+            .with_body(r#"{"node_id": "R_1", "name": "repo1"}"#)
+            .create_async()
+            .await;
+        let not_found_req = server
+            .mock("GET", "/repos/org/repo2")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), None).unwrap();
+        assert_eq!(
+            gh.repository_node_id("https://github.com/org/repo1/")
+                .await
+                .unwrap(),
+            Some("R_1".to_string())
+        );
+        assert_eq!(
+            gh.repository_node_id("https://github.com/org/repo2")
+                .await
+                .unwrap(),
+            None
+        );
+        found_req.assert_async().await;
+        not_found_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn repository_metadata_found_and_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let found_req = server
+            .mock("POST", "/graphql")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"variables": {"owner": "org", "name": "repo1"}}),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"data": {"repository": {
+                    "description": "Description",
+                    "homepageUrl": "",
+                    "openGraphImageUrl": "https://preview.url",
+                    "usesCustomOpenGraphImage": true,
+                    "contributingGuidelines": {"url": "https://contributing.url"}
+                }}}"#,
+            )
+            .create_async()
+            .await;
+        let not_found_req = server
+            .mock("POST", "/graphql")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"variables": {"owner": "org", "name": "repo2"}}),
+            ))
+            .with_status(200)
+            .with_body(r#"{"data": {"repository": null}, "errors": [{"type": "NOT_FOUND"}]}"#)
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), Some("token")).unwrap();
+        assert_eq!(
+            gh.repository_metadata("https://github.com/org/repo1")
+                .await
+                .unwrap(),
+            Some(RepositoryMetadata {
+                description: Some("Description".to_string()),
+                homepage_url: None,
+                social_preview_url: Some("https://preview.url".to_string()),
+                contributing_url: Some("https://contributing.url".to_string()),
+            })
+        );
+        assert_eq!(
+            gh.repository_metadata("https://github.com/org/repo2")
+                .await
+                .unwrap(),
+            None
+        );
+        found_req.assert_async().await;
+        not_found_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn repository_metadata_graphql_error() {
+        let mut server = mockito::Server::new_async().await;
+        let req = server
+            .mock("POST", "/graphql")
+            .with_status(200)
+            .with_body(
+                r#"{"errors": [{"type": "RATE_LIMITED", "message": "rate limit exceeded"}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), Some("token")).unwrap();
+        let result = gh.repository_metadata("https://github.com/org/repo1").await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "error getting repository https://github.com/org/repo1 metadata: rate limit exceeded"
+        );
+        req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn org_repositories_private_skipped() {
+        let mut server = mockito::Server::new_async().await;
+        let req = server
+            .mock("GET", "/orgs/org/repos")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"[
+                    {"name": "public", "html_url": "https://github.com/org/public", "private": false},
+                    {"name": "private", "html_url": "https://github.com/org/private", "private": true}
+                ]"#,
+            )
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), Some("token")).unwrap();
+        let repositories = gh.org_repositories("org").await.unwrap();
+        assert_eq!(
+            repositories,
+            vec![OrgRepository {
+                name: "public".to_string(),
+                html_url: "https://github.com/org/public".to_string(),
+                archived: false,
+                fork: false,
+                private: false,
+            }]
+        );
+        req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn org_repositories_unexpected_status() {
+        let mut server = mockito::Server::new_async().await;
+        let req = server
+            .mock("GET", "/orgs/org/repos")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let gh = GHApi::new(&server.url(), None).unwrap();
+        let result = gh.org_repositories("org").await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected status code listing org repositories: 404 Not Found"
+        );
+        req.assert_async().await;
+    }
+}
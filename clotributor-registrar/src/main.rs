@@ -1,37 +1,94 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::doc_markdown)]
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use anyhow::{Context, Result};
-use clap::Parser;
+use anyhow::{Context, Result, format_err};
+use clap::{Parser, Subcommand};
 use config::{Config, File};
 use deadpool_postgres::{Config as DbConfig, Runtime};
 use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
 use postgres_openssl::MakeTlsConnector;
 use tracing::debug;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{EnvFilter, fmt::writer::BoxMakeWriter};
 
-use crate::db::PgDB;
+use crate::{
+    datasource::DataFormat,
+    db::PgDB,
+    github::{GHApi, GITHUB_API_URL},
+    plan::PlanFormat,
+};
 
+mod datasource;
 mod db;
+mod github;
+mod plan;
 mod registrar;
+mod validation;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
 struct Args {
     /// Config file path
     #[clap(short, long)]
-    config: PathBuf,
+    config: Option<PathBuf>,
+
+    /// Unregister projects even if the maximum percentage allowed is exceeded
+    #[clap(long)]
+    force_unregister: bool,
+
+    /// Print the changes a run would apply without applying them
+    #[clap(long)]
+    plan: bool,
+
+    /// Plan output format
+    #[clap(long, value_enum, default_value_t, requires = "plan")]
+    output: PlanFormat,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Validate a foundation data file, reporting the problems found
+    Validate {
+        /// Data file path
+        file: PathBuf,
+
+        /// Data file format
+        #[clap(short, long, value_enum, default_value_t)]
+        format: DataFormat,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    // Validate data file if requested
+    if let Some(Command::Validate { file, format }) = args.command {
+        return validate(&file, format);
+    }
+
     // Setup configuration
+    let config = args
+        .config
+        .ok_or_else(|| format_err!("config file path not provided"))?;
     let cfg = Config::builder()
-        .add_source(File::from(args.config))
+        .set_default("registrar.maxUnregisterPercentage", 25)?
+        .set_default("registrar.forceUnregister", false)?
+        .set_default("registrar.unregisterGracePeriodDays", 7)?
+        .set_default("registrar.maxFailedPercentage", 10)?
+        .set_default("registrar.enrichProjects", false)?
+        .add_source(File::from(config))
+        .set_override_option(
+            "registrar.forceUnregister",
+            args.force_unregister.then_some(true),
+        )?
         .build()
         .context("error setting up configuration")?;
 
@@ -41,7 +98,15 @@ async fn main() -> Result<()> {
             std::env::set_var("RUST_LOG", "clotributor_registrar=debug");
         }
     }
-    let s = tracing_subscriber::fmt().with_env_filter(EnvFilter::from_default_env());
+    // The plan is printed to stdout, so logs are written to stderr instead
+    let writer = if args.plan {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let s = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(writer);
     match cfg.get_string("log.format").as_deref() {
         Ok("json") => s.json().init(),
         _ => s.init(),
@@ -56,8 +121,43 @@ async fn main() -> Result<()> {
     let pool = db_cfg.create_pool(Some(Runtime::Tokio1), connector)?;
     let db = Arc::new(PgDB::new(pool));
 
+    // Setup GitHub client
+    let gh_token = cfg.get_string("creds.githubToken").ok();
+    let gh = Arc::new(GHApi::new(GITHUB_API_URL, gh_token.as_deref())?);
+
+    // Print plan if requested
+    if args.plan {
+        let plan = plan::run(&cfg, db, gh).await?;
+        match args.output {
+            PlanFormat::Text => print!("{plan}"),
+            PlanFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+        }
+        return Ok(());
+    }
+
     // Run registrar
-    registrar::run(&cfg, db).await?;
+    registrar::run(&cfg, db, gh).await?;
 
     Ok(())
 }
+
+/// Validate the data file provided, printing the problems found.
+fn validate(file: &Path, format: DataFormat) -> Result<()> {
+    let data = std::fs::read_to_string(file)
+        .context(format!("error reading data file {}", file.display()))?;
+    let entries = datasource::parse_projects(format, &file.to_string_lossy(), &data)?;
+    let (projects, errors) = validation::validate(entries);
+
+    for err in &errors {
+        println!("{}: {err}", file.display());
+    }
+    println!(
+        "{} valid projects, {} problems found",
+        projects.len(),
+        errors.len()
+    );
+    if !errors.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,476 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use anyhow::{Context, Result, format_err};
+use clap::ValueEnum;
+use config::{Config, ConfigError};
+use serde::Serialize;
+use tracing::{debug, instrument};
+
+use crate::{
+    datasource::{self, DataFileCache},
+    db::DynDB,
+    github::DynGH,
+    registrar::{self, Foundation, FoundationConfig, Project, Repository, UnregisterOptions},
+};
+
+/// Format used to print the plan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum PlanFormat {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// JSON document.
+    Json,
+}
+
+/// Prepare a plan with the changes a registrar run would apply to the
+/// projects registered in the database, without applying them. Data files are
+/// always fetched, even if they have not changed since they were processed
+/// last time.
+#[instrument(skip_all, err)]
+pub(crate) async fn run(cfg: &Config, db: DynDB, gh: DynGH) -> Result<Plan> {
+    // Foundations declared in the config (if any) take precedence over the
+    // ones registered in the database, as they have not been reconciled yet
+    let foundations = match cfg.get::<Vec<FoundationConfig>>("foundations") {
+        Ok(foundations) => foundations
+            .into_iter()
+            .filter(|f| f.enabled)
+            .map(|f| Foundation {
+                foundation_id: f.foundation_id,
+                data_url: f.data_url,
+                data_format: f.data_format,
+                data_cache: DataFileCache::default(),
+                opt_in: f.opt_in,
+            })
+            .collect(),
+        Err(ConfigError::NotFound(_)) => db.foundations().await?,
+        Err(err) => return Err(err.into()),
+    };
+
+    let unregister_options = UnregisterOptions::from_config(cfg)?;
+    let enrich_projects = cfg.get("registrar.enrichProjects")?;
+    let http_client = reqwest::Client::new();
+    let mut plan = Plan::default();
+    for foundation in foundations {
+        let foundation_id = foundation.foundation_id.clone();
+        let foundation_plan = plan_foundation(
+            &db,
+            &gh,
+            &http_client,
+            foundation,
+            unregister_options,
+            enrich_projects,
+        )
+        .await
+        .context(format!(
+            "error preparing plan for foundation {foundation_id}"
+        ))?;
+        plan.foundations.push(foundation_plan);
+    }
+    plan.foundations
+        .sort_by(|a, b| a.foundation_id.cmp(&b.foundation_id));
+
+    Ok(plan)
+}
+
+/// Prepare the plan for the foundation provided.
+#[instrument(fields(foundation = foundation.foundation_id), skip_all, err)]
+async fn plan_foundation(
+    db: &DynDB,
+    gh: &DynGH,
+    http_client: &reqwest::Client,
+    foundation: Foundation,
+    unregister_options: UnregisterOptions,
+    enrich_projects: bool,
+) -> Result<FoundationPlan> {
+    debug!("started");
+
+    // Get projects available in the data file
+    let data_file =
+        datasource::fetch_data_file(http_client, &foundation.data_url, &DataFileCache::default())
+            .await?
+            .ok_or_else(|| format_err!("data file not available"))?;
+    let available =
+        registrar::available_projects(gh, &foundation, &data_file.content, enrich_projects).await?;
+
+    // Get projects registered in the database
+    let projects_registered = db.foundation_projects(&foundation.foundation_id).await?;
+    let repositories_registered = db
+        .foundation_repositories(&foundation.foundation_id)
+        .await?;
+
+    // Compare them
+    let mut plan = FoundationPlan {
+        foundation_id: foundation.foundation_id.clone(),
+        ..Default::default()
+    };
+    for (name, project) in &available.projects {
+        let registered_repositories = repositories_registered
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        match projects_registered.get(name) {
+            None => plan
+                .projects_added
+                .push(ProjectChanges::new(project, registered_repositories)),
+            Some(registered_digest) if registered_digest != &project.digest => plan
+                .projects_updated
+                .push(ProjectChanges::new(project, registered_repositories)),
+            Some(_) => {}
+        }
+    }
+    plan.projects_removed = available.projects_to_unregister(&projects_registered);
+    if let Err(err) =
+        unregister_options.check(plan.projects_removed.len(), projects_registered.len())
+    {
+        plan.error = Some(err.to_string());
+    }
+    plan.projects_added.sort_by(|a, b| a.name.cmp(&b.name));
+    plan.projects_updated.sort_by(|a, b| a.name.cmp(&b.name));
+    plan.projects_removed.sort();
+
+    debug!("completed");
+    Ok(plan)
+}
+
+/// Changes a registrar run would apply.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct Plan {
+    pub foundations: Vec<FoundationPlan>,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for foundation in &self.foundations {
+            writeln!(f, "foundation {}:", foundation.foundation_id)?;
+            for project in &foundation.projects_added {
+                writeln!(f, "  + {}", project.name)?;
+                project.fmt_changes(f)?;
+            }
+            for project in &foundation.projects_updated {
+                writeln!(f, "  ~ {}", project.name)?;
+                project.fmt_changes(f)?;
+            }
+            for name in &foundation.projects_removed {
+                writeln!(f, "  - {name}")?;
+            }
+            writeln!(
+                f,
+                "  {} to add, {} to update, {} to remove",
+                foundation.projects_added.len(),
+                foundation.projects_updated.len(),
+                foundation.projects_removed.len()
+            )?;
+            if let Some(error) = &foundation.error {
+                writeln!(f, "  ! {error} (no changes would be applied)")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Changes a registrar run would apply to a foundation's projects.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct FoundationPlan {
+    pub foundation_id: String,
+    pub projects_added: Vec<ProjectChanges>,
+    pub projects_updated: Vec<ProjectChanges>,
+    pub projects_removed: Vec<String>,
+    /// Error that would prevent the changes from being applied, like the
+    /// maximum percentage of projects to unregister being exceeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Changes a registrar run would apply to a project's repositories.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct ProjectChanges {
+    pub name: String,
+    pub repositories_added: Vec<String>,
+    pub repositories_removed: Vec<String>,
+    pub label_changes: Vec<LabelChange>,
+}
+
+impl ProjectChanges {
+    /// Create a new ProjectChanges instance comparing the project available
+    /// in the data file with the repositories registered in the database.
+    fn new(project: &Project, registered: &[Repository]) -> Self {
+        let find = |repositories: &[Repository], url: &str| {
+            repositories.iter().find(|r| r.url == url).cloned()
+        };
+
+        let mut changes = ProjectChanges {
+            name: project.name.clone(),
+            ..Default::default()
+        };
+        for repository in &project.repositories {
+            match find(registered, &repository.url) {
+                None => changes.repositories_added.push(repository.url.clone()),
+                Some(registered) => {
+                    changes
+                        .label_changes
+                        .extend(LabelChange::compare(&registered, repository));
+                }
+            }
+        }
+        for repository in registered {
+            if find(&project.repositories, &repository.url).is_none() {
+                changes.repositories_removed.push(repository.url.clone());
+            }
+        }
+        changes.repositories_added.sort();
+        changes.repositories_removed.sort();
+
+        changes
+    }
+
+    /// Format the project's changes (used when displaying the plan).
+    fn fmt_changes(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for url in &self.repositories_added {
+            writeln!(f, "      + repository {url}")?;
+        }
+        for url in &self.repositories_removed {
+            writeln!(f, "      - repository {url}")?;
+        }
+        for change in &self.label_changes {
+            writeln!(
+                f,
+                "      ~ repository {} {}: {} -> {}",
+                change.repository,
+                change.setting,
+                change.old.as_deref().unwrap_or("(none)"),
+                change.new.as_deref().unwrap_or("(none)")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Change in a repository's labels settings (issues filter label or label
+/// overrides).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct LabelChange {
+    pub repository: String,
+    pub setting: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl LabelChange {
+    /// Compare the labels settings of the registered and available versions of
+    /// a repository, returning the changes found.
+    fn compare(registered: &Repository, available: &Repository) -> Vec<LabelChange> {
+        let mut changes = vec![];
+        let non_empty = |label: &Option<String>| label.clone().filter(|l| !l.is_empty());
+
+        // Issues filter label
+        let (old, new) = (
+            non_empty(&registered.issues_filter_label),
+            non_empty(&available.issues_filter_label),
+        );
+        if old != new {
+            changes.push(LabelChange {
+                repository: available.url.clone(),
+                setting: "issues_filter_label".to_string(),
+                old,
+                new,
+            });
+        }
+
+        // Label overrides
+        let empty = BTreeMap::new();
+        let old_overrides = registered.label_overrides.as_ref().unwrap_or(&empty);
+        let new_overrides = available.label_overrides.as_ref().unwrap_or(&empty);
+        let labels: BTreeSet<&String> = old_overrides.keys().chain(new_overrides.keys()).collect();
+        for label in labels {
+            let (old, new) = (old_overrides.get(label), new_overrides.get(label));
+            if old != new {
+                changes.push(LabelChange {
+                    repository: available.url.clone(),
+                    setting: format!("label_overrides.{label}"),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::MockDB, github::MockGH};
+    use config::builder::{ConfigBuilder, DefaultState};
+    use futures::future;
+    use mockall::predicate::eq;
+    use std::{collections::HashMap, sync::Arc};
+
+    const TESTDATA_PATH: &str = "src/testdata";
+    const FOUNDATION: &str = "cncf";
+
+    #[tokio::test]
+    async fn plan_changes() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        let cfg = test_config_builder().build().unwrap();
+        let plan = run(&cfg, Arc::new(setup_test_db(url)), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+
+        assert_eq!(
+            plan,
+            Plan {
+                foundations: vec![FoundationPlan {
+                    foundation_id: FOUNDATION.to_string(),
+                    projects_added: vec![],
+                    projects_updated: vec![ProjectChanges {
+                        name: "artifact-hub".to_string(),
+                        repositories_added: vec![],
+                        repositories_removed: vec![
+                            "https://github.com/artifacthub/old".to_string()
+                        ],
+                        label_changes: vec![
+                            LabelChange {
+                                repository: "https://github.com/artifacthub/hub".to_string(),
+                                setting: "issues_filter_label".to_string(),
+                                old: Some("help wanted".to_string()),
+                                new: None,
+                            },
+                            LabelChange {
+                                repository: "https://github.com/artifacthub/hub".to_string(),
+                                setting: "label_overrides.beginner".to_string(),
+                                old: Some("good first issue".to_string()),
+                                new: None,
+                            },
+                        ],
+                    }],
+                    projects_removed: vec!["removed-project".to_string()],
+                    error: None,
+                }],
+            }
+        );
+        assert_eq!(
+            plan.to_string(),
+            "\
+foundation cncf:
+  ~ artifact-hub
+      - repository https://github.com/artifacthub/old
+      ~ repository https://github.com/artifacthub/hub issues_filter_label: help wanted -> (none)
+      ~ repository https://github.com/artifacthub/hub label_overrides.beginner: good first issue -> (none)
+  - removed-project
+  0 to add, 1 to update, 1 to remove
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn plan_unregister_threshold_exceeded() {
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        let cfg = test_config_builder()
+            .set_override("registrar.maxUnregisterPercentage", 25)
+            .unwrap()
+            .build()
+            .unwrap();
+        let plan = run(&cfg, Arc::new(setup_test_db(url)), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+
+        let foundation = &plan.foundations[0];
+        assert_eq!(
+            foundation.projects_removed,
+            vec!["removed-project".to_string()]
+        );
+        assert_eq!(
+            foundation.error.as_deref(),
+            Some(
+                "refusing to unregister 1 of 2 projects (50%), the maximum allowed is 25% (use --force-unregister to override)"
+            )
+        );
+        assert!(plan.to_string().ends_with(
+            "  0 to add, 1 to update, 1 to remove\n  ! refusing to unregister 1 of 2 projects (50%), the maximum allowed is 25% (use --force-unregister to override) (no changes would be applied)\n"
+        ));
+    }
+
+    fn setup_test_db(url: String) -> MockDB {
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: datasource::DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(HashMap::from([
+                    ("artifact-hub".to_string(), Some("old digest".to_string())),
+                    ("removed-project".to_string(), Some("digest".to_string())),
+                ]))))
+            });
+        db.expect_foundation_repositories()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                let repository = |url: &str, label: Option<&str>| Repository {
+                    name: "repo".to_string(),
+                    url: url.to_string(),
+                    exclude: None,
+                    issues_filter_label: label.map(ToString::to_string),
+                    include: None,
+                    label_overrides: Some(BTreeMap::from([(
+                        "beginner".to_string(),
+                        "good first issue".to_string(),
+                    )])),
+                    docs_only: Some(false),
+                    github_node_id: None,
+                };
+                Box::pin(future::ready(Ok(HashMap::from([(
+                    "artifact-hub".to_string(),
+                    vec![
+                        repository("https://github.com/artifacthub/hub", Some("help wanted")),
+                        repository("https://github.com/artifacthub/old", None),
+                    ],
+                )]))))
+            });
+
+        db
+    }
+
+    fn test_config_builder() -> ConfigBuilder<DefaultState> {
+        Config::builder()
+            .set_default("registrar.maxUnregisterPercentage", 50)
+            .unwrap()
+            .set_default("registrar.forceUnregister", false)
+            .unwrap()
+            .set_default("registrar.unregisterGracePeriodDays", 7)
+            .unwrap()
+            .set_default("registrar.enrichProjects", false)
+            .unwrap()
+    }
+}
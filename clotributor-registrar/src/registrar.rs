@@ -1,34 +1,71 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::Duration,
+};
 
 use anyhow::{Context, Error, Result, bail, format_err};
-use config::Config;
+use config::{Config, ConfigError};
 use futures::stream::{self, StreamExt};
-use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
 use tokio::time::{Instant, timeout};
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
+use wildmatch::WildMatch;
 
-use crate::db::DynDB;
+use crate::{
+    datasource::{self, DataFileCache, DataFormat},
+    db::DynDB,
+    github::{DynGH, OrgRepository, RepositoryMetadata},
+    validation,
+};
 
 /// Maximum time that can take processing a foundation data file.
 const FOUNDATION_TIMEOUT: u64 = 300;
 
+/// Name used in the data files to include or exclude projects and
+/// repositories from this service.
+const SERVICE_NAME: &str = "clotributor";
+
 /// Process foundations registered in the database.
 #[instrument(skip_all, err)]
-pub(crate) async fn run(cfg: &Config, db: DynDB) -> Result<()> {
+pub(crate) async fn run(cfg: &Config, db: DynDB, gh: DynGH) -> Result<()> {
     info!("started");
 
+    // Reconcile foundations declared in the config (if any) with the database
+    match cfg.get::<Vec<FoundationConfig>>("foundations") {
+        Ok(foundations) => {
+            debug!("reconciling foundations");
+            db.reconcile_foundations(&foundations)
+                .await
+                .context("error reconciling foundations")?;
+        }
+        Err(ConfigError::NotFound(_)) => {}
+        Err(err) => return Err(err.into()),
+    }
+
     // Process foundations
+    let unregister_options = UnregisterOptions::from_config(cfg)?;
+    let max_failed_percentage = cfg.get("registrar.maxFailedPercentage")?;
+    let enrich_projects = cfg.get("registrar.enrichProjects")?;
     let http_client = reqwest::Client::new();
     let foundations = db.foundations().await?;
     #[allow(clippy::manual_try_fold)]
     let result = stream::iter(foundations)
         .map(|foundation| async {
             let foundation_id = foundation.foundation_id.clone();
-            match timeout(
+            let mut run = Run::new();
+            let result = match timeout(
                 Duration::from_secs(FOUNDATION_TIMEOUT),
-                process_foundation(db.clone(), http_client.clone(), foundation),
+                process_foundation(
+                    db.clone(),
+                    gh.clone(),
+                    http_client.clone(),
+                    foundation,
+                    unregister_options,
+                    max_failed_percentage,
+                    enrich_projects,
+                ),
             )
             .await
             {
@@ -37,7 +74,21 @@ pub(crate) async fn run(cfg: &Config, db: DynDB) -> Result<()> {
             }
             .context(format!(
                 "error processing foundation {foundation_id} data file"
-            ))
+            ));
+
+            // Register run in database
+            match &result {
+                Ok(processed) => {
+                    run.processed = processed.projects;
+                    run.failures.clone_from(&processed.failures);
+                }
+                Err(err) => run.errors.push(format!("{err:#}")),
+            }
+            if let Err(err) = db.register_run(&foundation_id, &run).await {
+                error!(?err, foundation = foundation_id, "error registering run");
+            }
+
+            result.map(|_| ())
         })
         .buffer_unordered(cfg.get("registrar.concurrency")?)
         .collect::<Vec<Result<()>>>()
@@ -60,84 +111,404 @@ pub(crate) async fn run(cfg: &Config, db: DynDB) -> Result<()> {
 
 /// Process foundation's data file. New projects available will be registered
 /// in the database and existing ones which have changed will be updated. When
-/// a project is removed from the data file, it'll be removed from the database
-/// as well.
+/// a project is removed from the data file, it'll be unregistered from the
+/// database as well, unless too many projects would be unregistered at once.
+/// Unregistered projects are deleted permanently once the grace period has
+/// passed. When the data file has not changed since the last time it was
+/// processed successfully, projects are not processed again. All changes are
+/// applied in a single transaction: projects that fail are reported, and no
+/// changes are applied at all if the percentage of failures exceeds the
+/// maximum allowed.
 #[instrument(fields(foundation = foundation.foundation_id), skip_all, err)]
 async fn process_foundation(
     db: DynDB,
+    gh: DynGH,
     http_client: reqwest::Client,
     foundation: Foundation,
-) -> Result<()> {
+    unregister_options: UnregisterOptions,
+    max_failed_percentage: usize,
+    enrich_projects: bool,
+) -> Result<ProcessedFoundation> {
     let start = Instant::now();
     debug!("started");
 
-    // Fetch foundation data file
-    let resp = http_client.get(foundation.data_url).send().await?;
-    if resp.status() != StatusCode::OK {
+    // Fetch foundation data file (if it has changed)
+    let foundation_id = &foundation.foundation_id;
+    let Some(data_file) =
+        datasource::fetch_data_file(&http_client, &foundation.data_url, &foundation.data_cache)
+            .await?
+    else {
+        debug!("data file not modified, skipping");
+        db.purge_unregistered_projects(foundation_id, unregister_options.grace_period_days)
+            .await?;
+        let projects_registered = db.foundation_projects(foundation_id).await?;
+        return Ok(ProcessedFoundation {
+            projects: i32::try_from(projects_registered.len())?,
+            failures: vec![],
+        });
+    };
+
+    // Get projects available in the data file
+    let mut available =
+        available_projects(&gh, &foundation, &data_file.content, enrich_projects).await?;
+    let mut cacheable = available.cacheable;
+
+    // Get projects registered in the database
+    let projects_registered = db.foundation_projects(foundation_id).await?;
+
+    // Collect available projects to register or update as needed
+    let mut repositories_registered = None;
+    let mut projects_to_register = vec![];
+    for (name, project) in &mut available.projects {
+        // Check if the project is already registered
+        let registered_digest = projects_registered.get(name);
+        if registered_digest.is_some_and(|digest| digest == &project.digest) {
+            continue;
+        }
+
+        // Some of the project's repositories may have been renamed
+        if registered_digest.is_some() {
+            if repositories_registered.is_none() {
+                repositories_registered = Some(db.foundation_repositories(foundation_id).await?);
+            }
+            let registered = repositories_registered
+                .as_ref()
+                .and_then(|repositories| repositories.get(name))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            project.resolve_renamed_repositories(&gh, registered).await;
+        }
+
+        debug!(project = project.name, "registering");
+        projects_to_register.push(project.clone());
+    }
+
+    // Collect projects no longer available in the data file to unregister them
+    if available.skipped_unnamed {
+        warn!("some invalid entries could not be identified, skipping projects unregistration");
+    }
+    let projects_to_unregister = available.projects_to_unregister(&projects_registered);
+    unregister_options.check(projects_to_unregister.len(), projects_registered.len())?;
+    for name in &projects_to_unregister {
+        debug!(project = name, "unregistering");
+    }
+
+    // Apply all changes in a single transaction (at least one failure is
+    // allowed when the percentage is not zero, so that small foundations are
+    // not blocked by a single failing project)
+    let changes = projects_to_register.len() + projects_to_unregister.len();
+    let sync = FoundationSync {
+        foundation_id: foundation_id.clone(),
+        projects_to_register,
+        projects_to_unregister,
+        max_failures: (changes * max_failed_percentage).div_ceil(100),
+    };
+    let failures = apply_changes(&db, &sync, max_failed_percentage).await?;
+    if !failures.is_empty() {
+        cacheable = false;
+    }
+
+    // Purge projects unregistered longer than the grace period ago
+    let purged = db
+        .purge_unregistered_projects(foundation_id, unregister_options.grace_period_days)
+        .await?;
+    if purged > 0 {
+        debug!(purged, "unregistered projects purged");
+    }
+
+    // Keep track of the data file processed, so that it can be skipped next
+    // time if it has not changed
+    if cacheable {
+        db.update_foundation_data_cache(foundation_id, &data_file.cache)
+            .await?;
+    }
+
+    debug!(duration_secs = start.elapsed().as_secs(), "completed");
+    Ok(ProcessedFoundation {
+        projects: i32::try_from(available.projects.len())?,
+        failures,
+    })
+}
+
+/// Apply the foundation changes provided in the database. The changes are not
+/// committed if the percentage of projects that fail exceeds the maximum
+/// allowed, in which case an error is returned. Otherwise, the failures (if
+/// any) are returned.
+async fn apply_changes(
+    db: &DynDB,
+    sync: &FoundationSync,
+    max_failed_percentage: usize,
+) -> Result<Vec<String>> {
+    let changes = sync.projects_to_register.len() + sync.projects_to_unregister.len();
+    if changes == 0 {
+        return Ok(vec![]);
+    }
+
+    let failures: Vec<String> = db
+        .sync_foundation(sync)
+        .await?
+        .into_iter()
+        .map(|failure| format!("{}: {}", failure.project, failure.error))
+        .collect();
+    if failures.len() > sync.max_failures {
         bail!(
-            "unexpected status code getting data file: {}",
-            resp.status()
+            "{} of {changes} project changes failed, the maximum allowed is {max_failed_percentage}% (no changes were applied):\n{}",
+            failures.len(),
+            failures.join("\n")
         );
     }
-    let data = resp.text().await?;
+    for failure in &failures {
+        error!(failure, "error applying project changes");
+    }
 
-    // Get projects available in the data file
-    let tmp: Vec<Project> = serde_yaml::from_str(&data)?;
-    let mut projects_available: HashMap<String, Project> = HashMap::with_capacity(tmp.len());
+    Ok(failures)
+}
+
+/// Get the projects available in the foundation's data file content provided,
+/// ready to be registered. Invalid projects are skipped, as well as the ones
+/// (or their repositories) excluded for this service. Repositories discovered
+/// in the projects' GitHub organizations are added, and projects are enriched
+/// with some metadata from GitHub when requested.
+pub(crate) async fn available_projects(
+    gh: &DynGH,
+    foundation: &Foundation,
+    content: &str,
+    enrich: bool,
+) -> Result<AvailableProjects> {
+    // Get projects available in the data file, skipping the invalid ones
+    let entries =
+        datasource::parse_projects(foundation.data_format, &foundation.data_url, content)?;
+    let (tmp, validation_errors) = validation::validate(entries);
+    let mut skipped = HashSet::new();
+    let mut skipped_unnamed = false;
+    for err in validation_errors {
+        warn!(project = err.project, "skipping invalid project: {err}");
+        if let Some(name) = err.project {
+            skipped.insert(name);
+        } else {
+            skipped_unnamed = true;
+        }
+    }
+
+    // The data file cannot be skipped next time if repositories are discovered
+    // or projects enriched dynamically, as they may change even if the data
+    // file does not
+    let mut cacheable = true;
+    let mut projects: HashMap<String, Project> = HashMap::with_capacity(tmp.len());
     for mut project in tmp {
+        // Do not include projects that have been excluded for this service
+        if project.is_excluded() {
+            debug!(project = project.name, "project excluded, skipping");
+            continue;
+        }
+
+        // Add repositories discovered in the project's GitHub organizations
+        if project.github_orgs.is_some() {
+            cacheable = false;
+        }
+        let listed_repositories = project.repositories.len();
+        project.add_github_orgs_repositories(gh).await?;
+        let discovered_repositories = project.repositories.len() - listed_repositories;
+
+        // When the foundation uses the opt-in mode, only the repositories that
+        // have been explicitly included for this service are processed, unless
+        // the whole project has been included (the repositories discovered in
+        // GitHub organizations can only be included this way)
+        if foundation.opt_in && !project.is_included() {
+            if discovered_repositories > 0 {
+                warn!(
+                    project = project.name,
+                    discovered_repositories,
+                    "repositories discovered in github orgs ignored, the project has not opted in"
+                );
+            }
+            project.repositories.retain(Repository::is_included);
+            if project.repositories.is_empty() {
+                debug!(project = project.name, "no repositories opted in, skipping");
+                continue;
+            }
+        }
+
         // Do not include repositories that have been excluded for this service
-        project.repositories.retain(|r| {
-            if let Some(exclude) = &r.exclude {
-                return !exclude.contains(&"clotributor".to_string());
+        project.repositories.retain(|r| !r.is_excluded());
+
+        // Enrich project with some metadata from GitHub if enabled and the
+        // data file does not provide a description or logo (it's part of the
+        // digest, so changes in GitHub are picked up as well). Enrichment is
+        // optional, so projects that cannot be enriched are registered anyway,
+        // keeping the enrichment data stored previously (if any)
+        if enrich && project.needs_enrichment() {
+            cacheable = false;
+            if let Err(err) = project.enrich(gh).await {
+                warn!(?err, project = project.name, "error enriching project");
+                project.enrichment_failed = true;
             }
-            true
-        });
+        }
 
         project.set_digest()?;
-        projects_available.insert(project.name.clone(), project);
+        projects.insert(project.name.clone(), project);
     }
 
-    // Get projects registered in the database
-    let foundation_id = &foundation.foundation_id;
-    let projects_registered = db.foundation_projects(foundation_id).await?;
+    Ok(AvailableProjects {
+        projects,
+        skipped,
+        skipped_unnamed,
+        cacheable,
+    })
+}
 
-    // Register or update available projects as needed
-    for (name, project) in &projects_available {
-        // Check if the project is already registered
-        if let Some(registered_digest) = projects_registered.get(name)
-            && registered_digest == &project.digest
-        {
-            continue;
-        }
+/// Projects available in a foundation's data file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AvailableProjects {
+    /// Projects ready to be registered, indexed by name.
+    pub projects: HashMap<String, Project>,
+    /// Names of the invalid projects skipped.
+    pub skipped: HashSet<String>,
+    /// Whether some invalid entries were skipped without knowing the project
+    /// they belong to.
+    pub skipped_unnamed: bool,
+    /// Whether the data file can be skipped next time if it does not change.
+    pub cacheable: bool,
+}
 
-        // Register project
-        debug!(project = project.name, "registering");
-        if let Err(err) = db.register_project(foundation_id, project).await {
-            error!(?err, project = project.name, "error registering");
+impl AvailableProjects {
+    /// Return the names of the registered projects provided that are no longer
+    /// available and should be unregistered. Nothing is unregistered when no
+    /// projects are available, or when some invalid entries could not be
+    /// identified, as the projects they belong to would be unregistered.
+    pub(crate) fn projects_to_unregister(
+        &self,
+        projects_registered: &HashMap<String, Option<String>>,
+    ) -> Vec<String> {
+        if self.projects.is_empty() || self.skipped_unnamed {
+            return vec![];
         }
+        projects_registered
+            .keys()
+            .filter(|name| !self.projects.contains_key(*name) && !self.skipped.contains(*name))
+            .cloned()
+            .collect()
     }
+}
 
-    // Unregister projects no longer available in the data file
-    if !projects_available.is_empty() {
-        for name in projects_registered.keys() {
-            if !projects_available.contains_key(name) {
-                debug!(project = name, "unregistering");
-                if let Err(err) = db.unregister_project(foundation_id, name).await {
-                    error!(?err, project = name, "error unregistering");
-                }
-            }
+/// Result of processing a foundation's data file.
+#[derive(Debug, Clone, PartialEq)]
+struct ProcessedFoundation {
+    /// Number of projects available in the data file.
+    projects: i32,
+    /// Project changes that could not be applied.
+    failures: Vec<String>,
+}
+
+/// Changes to apply to a foundation's projects in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FoundationSync {
+    pub foundation_id: String,
+    pub projects_to_register: Vec<Project>,
+    pub projects_to_unregister: Vec<String>,
+    /// Maximum number of projects that can fail for the changes to be applied.
+    pub max_failures: usize,
+}
+
+/// Represents a project that could not be registered or unregistered.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProjectFailure {
+    pub project: String,
+    pub error: String,
+}
+
+/// Options used to safeguard projects unregistration.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UnregisterOptions {
+    /// Maximum percentage of the registered projects that can be unregistered
+    /// in a single run.
+    max_percentage: usize,
+    /// Unregister projects even if the maximum percentage is exceeded.
+    force: bool,
+    /// Days unregistered projects are kept before being deleted permanently.
+    grace_period_days: i32,
+}
+
+impl UnregisterOptions {
+    /// Create a new UnregisterOptions instance from the configuration.
+    pub(crate) fn from_config(cfg: &Config) -> Result<Self> {
+        Ok(Self {
+            max_percentage: cfg.get("registrar.maxUnregisterPercentage")?,
+            force: cfg.get("registrar.forceUnregister")?,
+            grace_period_days: cfg.get("registrar.unregisterGracePeriodDays")?,
+        })
+    }
+
+    /// Check if the number of projects provided can be unregistered, returning
+    /// an error when the maximum percentage allowed would be exceeded.
+    pub(crate) fn check(&self, to_unregister: usize, registered: usize) -> Result<()> {
+        if to_unregister == 0 || self.force {
+            return Ok(());
         }
+        let percentage = to_unregister * 100 / registered.max(1);
+        if percentage > self.max_percentage {
+            bail!(
+                "refusing to unregister {to_unregister} of {registered} projects ({percentage}%), the maximum allowed is {}% (use --force-unregister to override)",
+                self.max_percentage
+            );
+        }
+        Ok(())
     }
+}
 
-    debug!(duration_secs = start.elapsed().as_secs(), "completed");
-    Ok(())
+/// Represents a registrar run for a given foundation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Run {
+    pub started_at: OffsetDateTime,
+    pub processed: i32,
+    pub errors: Vec<String>,
+    pub failures: Vec<String>,
+}
+
+impl Run {
+    /// Create a new Run instance.
+    fn new() -> Self {
+        Self {
+            started_at: OffsetDateTime::now_utc(),
+            processed: 0,
+            errors: vec![],
+            failures: vec![],
+        }
+    }
 }
 
 /// Represents a foundation registered in the database.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_field_names)]
 pub(crate) struct Foundation {
     pub foundation_id: String,
     pub data_url: String,
+    #[serde(default)]
+    pub data_format: DataFormat,
+    #[serde(default)]
+    pub data_cache: DataFileCache,
+    #[serde(default)]
+    pub opt_in: bool,
+}
+
+/// Represents a foundation declared in the registrar's configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub(crate) struct FoundationConfig {
+    pub foundation_id: String,
+    pub display_name: String,
+    pub data_url: String,
+    #[serde(default)]
+    pub data_format: DataFormat,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub opt_in: bool,
+}
+
+/// Foundations declared in the configuration are enabled by default.
+fn default_enabled() -> bool {
+    true
 }
 
 /// Represents a project to be registered or updated.
@@ -165,6 +536,12 @@ pub(crate) struct Project {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub maturity: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subcategory: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub maintainers_wanted: Option<MaintainersWanted>,
 
@@ -172,9 +549,138 @@ pub(crate) struct Project {
     pub digest: Option<String>,
 
     pub repositories: Vec<Repository>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_orgs: Option<Vec<GithubOrg>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<RepositoryMetadata>,
+
+    /// Whether enriching the project failed. The enrichment data stored
+    /// previously (if any) is kept when the project is registered.
+    #[serde(
+        default,
+        skip_deserializing,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub enrichment_failed: bool,
 }
 
 impl Project {
+    /// Add the repositories owned by the project's GitHub organizations that
+    /// match the organization's filters. Repositories already listed in the
+    /// project explicitly take precedence over the discovered ones.
+    async fn add_github_orgs_repositories(&mut self, gh: &DynGH) -> Result<()> {
+        let Some(github_orgs) = &self.github_orgs else {
+            return Ok(());
+        };
+
+        for github_org in github_orgs {
+            let mut org_repositories =
+                gh.org_repositories(&github_org.name)
+                    .await
+                    .context(format!(
+                        "error getting repositories from github org {} (project {})",
+                        github_org.name, self.name
+                    ))?;
+            org_repositories.sort_by(|a, b| a.name.cmp(&b.name));
+            for org_repository in org_repositories {
+                if !github_org.matches(&org_repository)
+                    || self.repositories.iter().any(|r| {
+                        r.url.trim_end_matches('/') == org_repository.html_url.trim_end_matches('/')
+                    })
+                {
+                    continue;
+                }
+                self.repositories.push(Repository {
+                    name: org_repository.name,
+                    url: org_repository.html_url,
+                    exclude: None,
+                    issues_filter_label: github_org.issues_filter_label.clone(),
+                    include: None,
+                    label_overrides: None,
+                    docs_only: None,
+                    github_node_id: None,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enrich the project with some metadata from its primary repository (the
+    /// first one listed). Enrichment data is stored separately, so it never
+    /// overrides the values provided in the data file.
+    async fn enrich(&mut self, gh: &DynGH) -> Result<()> {
+        let Some(repository) = self.repositories.first() else {
+            return Ok(());
+        };
+        self.enrichment = gh.repository_metadata(&repository.url).await?;
+        Ok(())
+    }
+
+    /// Check if the project is missing some information that could be
+    /// obtained by enriching it (a logo or a description). Descriptions that
+    /// just repeat the project's name (some data files use it as a fallback)
+    /// are considered missing.
+    fn needs_enrichment(&self) -> bool {
+        self.logo_url.is_none() || self.has_placeholder_description()
+    }
+
+    /// Check if the project's description just repeats the project's name.
+    fn has_placeholder_description(&self) -> bool {
+        self.description == *self.display_name.as_ref().unwrap_or(&self.name)
+    }
+
+    /// Resolve the GitHub node id of the repositories not registered yet when
+    /// some of the registered ones are no longer listed, as they may have been
+    /// renamed. Registered repositories with the same node id will be updated
+    /// instead of being deleted and registered again (losing their issues).
+    async fn resolve_renamed_repositories(&mut self, gh: &DynGH, registered: &[Repository]) {
+        let is_listed =
+            |repositories: &[Repository], url: &str| repositories.iter().any(|r| r.url == url);
+        if !registered
+            .iter()
+            .any(|r| r.github_node_id.is_some() && !is_listed(&self.repositories, &r.url))
+        {
+            return;
+        }
+        for repository in &mut self.repositories {
+            if is_listed(registered, &repository.url) {
+                continue;
+            }
+            match gh.repository_node_id(&repository.url).await {
+                Ok(node_id) => repository.github_node_id = node_id,
+                Err(err) => warn!(
+                    ?err,
+                    url = repository.url,
+                    "error getting repository node id"
+                ),
+            }
+        }
+    }
+
+    /// Check if the project has been excluded for this service.
+    fn is_excluded(&self) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.iter().any(|s| s == SERVICE_NAME))
+    }
+
+    /// Check if the project has been explicitly included for this service
+    /// (which includes all its repositories).
+    fn is_included(&self) -> bool {
+        self.include
+            .as_ref()
+            .is_some_and(|include| include.iter().any(|s| s == SERVICE_NAME))
+    }
+
     /// Set the project's digest.
     fn set_digest(&mut self) -> Result<()> {
         let data = bincode::serde::encode_to_vec(&self, bincode::config::legacy())?;
@@ -195,6 +701,92 @@ pub(crate) struct Repository {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub issues_filter_label: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+
+    /// Repository labels mapped to the labels this service understands (i.e.
+    /// `beginner: good first issue`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_overrides: Option<BTreeMap<String, String>>,
+
+    /// The repository only contains documentation, so all its issues belong
+    /// to the docs area.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs_only: Option<bool>,
+
+    /// GitHub node id, used to keep track of the repository when it is
+    /// renamed (not read from the data files).
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub github_node_id: Option<String>,
+}
+
+impl Repository {
+    /// Check if the repository has been excluded for this service.
+    fn is_excluded(&self) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.iter().any(|s| s == SERVICE_NAME))
+    }
+
+    /// Check if the repository has been explicitly included for this service.
+    fn is_included(&self) -> bool {
+        self.include
+            .as_ref()
+            .is_some_and(|include| include.iter().any(|s| s == SERVICE_NAME))
+    }
+}
+
+/// Represents a GitHub organization whose repositories will be added to the
+/// project. Repositories can be selected using include and exclude glob
+/// patterns matched against the repository name. Archived repositories and
+/// forks are skipped unless requested otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct GithubOrg {
+    #[serde(rename = "github_org")]
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub include_archived: bool,
+
+    #[serde(default)]
+    pub include_forks: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issues_filter_label: Option<String>,
+}
+
+impl GithubOrg {
+    /// Check if the organization's repository provided matches the filters.
+    fn matches(&self, repository: &OrgRepository) -> bool {
+        if (repository.archived && !self.include_archived)
+            || (repository.fork && !self.include_forks)
+        {
+            return false;
+        }
+        let matches_any = |patterns: &Vec<String>| {
+            patterns
+                .iter()
+                .any(|pattern| WildMatch::new(pattern).matches(&repository.name))
+        };
+        if let Some(include) = &self.include
+            && !matches_any(include)
+        {
+            return false;
+        }
+        if let Some(exclude) = &self.exclude
+            && matches_any(exclude)
+        {
+            return false;
+        }
+        true
+    }
 }
 
 /// Defines if the project is looking for maintainers, as well as some extra
@@ -202,30 +794,31 @@ pub(crate) struct Repository {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) struct MaintainersWanted {
-    enabled: bool,
+    pub enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
-    links: Option<Vec<Link>>,
+    pub links: Option<Vec<Link>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    contacts: Option<Vec<Contact>>,
+    pub contacts: Option<Vec<Contact>>,
 }
 
 /// Represents some information about a link.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Link {
-    title: Option<String>,
-    url: String,
+    pub title: Option<String>,
+    pub url: String,
 }
 
 /// Represents some information about a contact.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub(crate) struct Contact {
-    github_handle: String,
+    pub github_handle: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::MockDB;
+    use crate::{datasource::DATA_CACHE_VERSION, db::MockDB, github::MockGH};
+    use config::builder::{ConfigBuilder, DefaultState};
     use futures::future;
     use mockall::predicate::eq;
     use std::sync::Arc;
@@ -243,7 +836,7 @@ mod tests {
             .times(1)
             .returning(|| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
 
-        let result = run(&cfg, Arc::new(db)).await;
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
         assert_eq!(result.unwrap_err().to_string(), FAKE_ERROR);
     }
 
@@ -256,7 +849,63 @@ mod tests {
             .times(1)
             .returning(|| Box::pin(future::ready(Ok(vec![]))));
 
-        run(&cfg, Arc::new(db)).await.unwrap();
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn reconcile_foundations_declared_in_config() {
+        let cfg = test_config_builder()
+            .add_source(config::File::from_str(
+                r"
+                foundations:
+                  - foundationId: cncf
+                    displayName: CNCF
+                    dataUrl: https://data.url/cncf.yaml
+                  - foundationId: lfai
+                    displayName: LF AI & Data
+                    dataUrl: https://data.url/landscape.yml
+                    dataFormat: landscape
+                    enabled: false
+                ",
+                config::FileFormat::Yaml,
+            ))
+            .build()
+            .unwrap();
+
+        let mut db = MockDB::new();
+        db.expect_reconcile_foundations()
+            .withf(|foundations| {
+                foundations
+                    == [
+                        FoundationConfig {
+                            foundation_id: "cncf".to_string(),
+                            display_name: "CNCF".to_string(),
+                            data_url: "https://data.url/cncf.yaml".to_string(),
+                            data_format: DataFormat::Clomonitor,
+                            enabled: true,
+                            opt_in: false,
+                        },
+                        FoundationConfig {
+                            foundation_id: "lfai".to_string(),
+                            display_name: "LF AI & Data".to_string(),
+                            data_url: "https://data.url/landscape.yml".to_string(),
+                            data_format: DataFormat::Landscape,
+                            enabled: false,
+                            opt_in: false,
+                        },
+                    ]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_foundations()
+            .times(1)
+            .returning(|| Box::pin(future::ready(Ok(vec![]))));
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
@@ -271,16 +920,26 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
 
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.len() == 1
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
         let data_file_req = server
             .mock("GET", "/")
             .with_status(404)
             .create_async()
             .await;
 
-        let result = run(&cfg, Arc::new(db)).await;
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
         assert_eq!(
             result.unwrap_err().root_cause().to_string(),
             "unexpected status code getting data file: 404 Not Found"
@@ -300,9 +959,19 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
 
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.len() == 1
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
         let data_file_req = server
             .mock("GET", "/")
             .with_status(200)
@@ -310,7 +979,7 @@ mod tests {
             .create_async()
             .await;
 
-        let result = run(&cfg, Arc::new(db)).await;
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
         assert_eq!(
             result.unwrap_err().root_cause().to_string(),
             "invalid type: map, expected a sequence"
@@ -318,6 +987,61 @@ mod tests {
         data_file_req.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn foundation_data_file_not_modified() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache {
+                    etag: Some("etag".to_string()),
+                    last_modified: None,
+                    digest: Some("digest".to_string()),
+                    version: Some(DATA_CACHE_VERSION),
+                },
+                opt_in: false,
+            }])))
+        });
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                let mut projects_registered = HashMap::new();
+                projects_registered.insert("artifact-hub".to_string(), Some("digest".to_string()));
+                Box::pin(future::ready(Ok(projects_registered)))
+            });
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .match_header("if-none-match", "etag")
+            .with_status(304)
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
     #[tokio::test]
     async fn error_getting_projects_registered_in_database() {
         let cfg = setup_test_config();
@@ -330,6 +1054,9 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
         db.expect_foundation_projects()
@@ -337,6 +1064,13 @@ mod tests {
             .times(1)
             .returning(|_| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
 
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.len() == 1
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
         let data_file_req = server
             .mock("GET", "/")
             .with_status(200)
@@ -344,7 +1078,7 @@ mod tests {
             .create_async()
             .await;
 
-        let result = run(&cfg, Arc::new(db)).await;
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
         assert_eq!(result.unwrap_err().root_cause().to_string(), FAKE_ERROR);
         data_file_req.assert_async().await;
     }
@@ -361,6 +1095,9 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
         db.expect_foundation_projects()
@@ -371,13 +1108,31 @@ mod tests {
                 projects_registered.insert(
                     "artifact-hub".to_string(),
                     Some(
-                        "fa26e52492428be17cb753516b2f8aabc7b9ceb43c3f3d5706ad155ca7747840"
+                        "f7561fa613e158ed407cfff05cd47c091d14f106053a35a18a1b0563005010b1"
                             .to_string(),
                     ),
                 );
                 Box::pin(future::ready(Ok(projects_registered)))
             });
 
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .withf(|foundation_id, data_cache| {
+                foundation_id == FOUNDATION && data_cache.digest.is_some()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
         let data_file_req = server
             .mock("GET", "/")
             .with_status(200)
@@ -385,7 +1140,9 @@ mod tests {
             .create_async()
             .await;
 
-        run(&cfg, Arc::new(db)).await.unwrap();
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
         data_file_req.assert_async().await;
     }
 
@@ -401,16 +1158,19 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
         db.expect_foundation_projects()
             .with(eq(FOUNDATION))
             .times(1)
             .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
-        db.expect_register_project()
-            .with(
-                eq(FOUNDATION),
-                eq(Project {
+        db.expect_sync_foundation()
+            .with(eq(FoundationSync {
+                foundation_id: FOUNDATION.to_string(),
+                projects_to_register: vec![Project {
                     name: "artifact-hub".to_string(),
                     display_name: Some("Artifact Hub".to_string()),
                     description: "Artifact Hub is a web-based application that enables finding, installing, and publishing packages and configurations for CNCF projects".to_string(),
@@ -419,16 +1179,47 @@ mod tests {
                     devstats_url: Some("https://artifacthub.devstats.cncf.io/".to_string()),
                     accepted_at: Some("2020-06-23".to_string()),
                     maturity: Some("sandbox".to_string()),
-                    digest: Some("fa26e52492428be17cb753516b2f8aabc7b9ceb43c3f3d5706ad155ca7747840".to_string()),
+                    category: Some("app definition".to_string()),
+                    subcategory: None,
+                    digest: Some("f7561fa613e158ed407cfff05cd47c091d14f106053a35a18a1b0563005010b1".to_string()),
                     repositories: vec![Repository{
                         name: "artifact-hub".to_string(),
                         url: "https://github.com/artifacthub/hub".to_string(),
                         exclude: None,
                         issues_filter_label: None,
+                        include: None,
+                        label_overrides: None,
+                        docs_only: None,
+                        github_node_id: None,
                     }],
                     maintainers_wanted: None,
-                }),
-            )
+                    github_orgs: None,
+                    exclude: None,
+                    include: None,
+                    enrichment: None,
+                    enrichment_failed: false,
+                }],
+                projects_to_unregister: vec![],
+                max_failures: 0,
+            }))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .withf(|foundation_id, data_cache| {
+                foundation_id == FOUNDATION && data_cache.digest.is_some()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
             .times(1)
             .returning(|_, _| Box::pin(future::ready(Ok(()))));
 
@@ -439,12 +1230,14 @@ mod tests {
             .create_async()
             .await;
 
-        run(&cfg, Arc::new(db)).await.unwrap();
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
         data_file_req.assert_async().await;
     }
 
     #[tokio::test]
-    async fn unregister_registered_project() {
+    async fn register_project_with_github_org_repositories() {
         let cfg = setup_test_config();
 
         let mut server = mockito::Server::new_async().await;
@@ -455,25 +1248,975 @@ mod tests {
             Box::pin(future::ready(Ok(vec![Foundation {
                 foundation_id: FOUNDATION.to_string(),
                 data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
             }])))
         });
         db.expect_foundation_projects()
             .with(eq(FOUNDATION))
             .times(1)
-            .returning(|_| {
-                let mut projects_registered = HashMap::new();
-                projects_registered.insert(
-                    "artifact-hub".to_string(),
-                    Some(
-                        "fa26e52492428be17cb753516b2f8aabc7b9ceb43c3f3d5706ad155ca7747840"
-                            .to_string(),
-                    ),
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let project = &sync.projects_to_register[0];
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_register.len() == 1
+                    && project.repositories
+                        == vec![
+                            Repository {
+                                name: "artifact-hub".to_string(),
+                                url: "https://github.com/artifacthub/hub".to_string(),
+                                exclude: None,
+                                issues_filter_label: None,
+                                include: None,
+                                label_overrides: None,
+                                docs_only: None,
+                                github_node_id: None,
+                            },
+                            Repository {
+                                name: "blog".to_string(),
+                                url: "https://github.com/artifacthub/blog".to_string(),
+                                exclude: None,
+                                issues_filter_label: Some("good first issue".to_string()),
+                                include: None,
+                                label_overrides: None,
+                                docs_only: None,
+                                github_node_id: None,
+                            },
+                        ]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_org_repositories()
+            .with(eq("artifacthub"))
+            .times(1)
+            .returning(|_| {
+                let repository = |name: &str, archived: bool, fork: bool| OrgRepository {
+                    name: name.to_string(),
+                    html_url: format!("https://github.com/artifacthub/{name}"),
+                    archived,
+                    fork,
+                    private: false,
+                };
+                Box::pin(future::ready(Ok(vec![
+                    repository("hub", false, false),
+                    repository("blog", false, false),
+                    repository("blog-old", false, false),
+                    repository("archived", true, false),
+                    repository("fork", false, true),
+                ])))
+            });
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-github-org.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn opt_in_foundation_and_github_org_repositories() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: true,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_org_repositories()
+            .with(eq("artifacthub"))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![OrgRepository {
+                    name: "blog".to_string(),
+                    html_url: "https://github.com/artifacthub/blog".to_string(),
+                    archived: false,
+                    fork: false,
+                    private: false,
+                }])))
+            });
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-github-org.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn opt_in_foundation_and_included_project_github_org_repositories() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: true,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+
+        // Projects that opt in include the repositories discovered as well
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                sync.projects_to_register.len() == 1
+                    && sync.projects_to_register[0]
+                        .repositories
+                        .iter()
+                        .map(|r| r.url.as_str())
+                        .collect::<Vec<_>>()
+                        == vec![
+                            "https://github.com/artifacthub/hub",
+                            "https://github.com/artifacthub/blog",
+                        ]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_org_repositories()
+            .with(eq("artifacthub"))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![OrgRepository {
+                    name: "blog".to_string(),
+                    html_url: "https://github.com/artifacthub/blog".to_string(),
+                    archived: false,
+                    fork: false,
+                    private: false,
+                }])))
+            });
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-github-org-opt-in.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn error_getting_github_org_repositories() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.len() == 1
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_org_repositories()
+            .with(eq("artifacthub"))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-github-org.yaml"))
+            .create_async()
+            .await;
+
+        let result = run(&cfg, Arc::new(db), Arc::new(gh)).await;
+        assert_eq!(result.unwrap_err().root_cause().to_string(), FAKE_ERROR);
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn invalid_projects_are_skipped() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                let mut projects_registered = HashMap::new();
+                projects_registered.insert("project2".to_string(), Some("digest".to_string()));
+                Box::pin(future::ready(Ok(projects_registered)))
+            });
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let project = &sync.projects_to_register[0];
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_register.len() == 1
+                    && project.name == "project1"
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .withf(|foundation_id, data_cache| {
+                foundation_id == FOUNDATION && data_cache.digest.is_some()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-invalid.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn register_project_with_renamed_repository() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        let mut gh = MockGH::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(HashMap::from([(
+                    "artifact-hub".to_string(),
+                    Some("old digest".to_string()),
+                )]))))
+            });
+        db.expect_foundation_repositories()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(HashMap::from([(
+                    "artifact-hub".to_string(),
+                    vec![Repository {
+                        name: "artifact-hub".to_string(),
+                        url: "https://github.com/artifacthub/old-hub".to_string(),
+                        exclude: None,
+                        issues_filter_label: None,
+                        include: None,
+                        label_overrides: None,
+                        docs_only: Some(false),
+                        github_node_id: Some("R_1".to_string()),
+                    }],
+                )]))))
+            });
+        gh.expect_repository_node_id()
+            .with(eq("https://github.com/artifacthub/hub"))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(Some("R_1".to_string())))));
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let project = &sync.projects_to_register[0];
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_register.len() == 1
+                    && project.repositories[0].url == "https://github.com/artifacthub/hub"
+                    && project.repositories[0].github_node_id == Some("R_1".to_string())
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn register_project_with_enrichment() {
+        let cfg = test_config_builder()
+            .set_override("registrar.enrichProjects", true)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let mut projects = sync.projects_to_register.clone();
+                projects.sort_by(|a, b| a.name.cmp(&b.name));
+                projects.len() == 2
+                    && projects[0].enrichment
+                        == Some(RepositoryMetadata {
+                            description: Some("Repository description".to_string()),
+                            homepage_url: Some("https://homepage.url".to_string()),
+                            social_preview_url: None,
+                            contributing_url: None,
+                        })
+                    // Projects providing a description and logo are not enriched
+                    && projects[1].enrichment.is_none()
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+
+        // Data file is not cached, as the enrichment data may change
+        db.expect_update_foundation_data_cache().times(0);
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 2 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_repository_metadata()
+            .with(eq("https://github.com/artifacthub/hub"))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(Some(RepositoryMetadata {
+                    description: Some("Repository description".to_string()),
+                    homepage_url: Some("https://homepage.url".to_string()),
+                    social_preview_url: None,
+                    contributing_url: None,
+                }))))
+            });
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-enrichment.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn error_enriching_project() {
+        let cfg = test_config_builder()
+            .set_override("registrar.enrichProjects", true)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+
+        // The project that could not be enriched is registered anyway (the
+        // enrichment data stored previously, if any, is kept)
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let mut projects: Vec<_> = sync
+                    .projects_to_register
+                    .iter()
+                    .map(|p| (p.name.as_str(), p.enrichment.is_some(), p.enrichment_failed))
+                    .collect();
+                projects.sort_unstable();
+                projects == vec![("artifact-hub", false, true), ("opa", false, false)]
+                    && sync.projects_to_unregister.is_empty()
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache().times(0);
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 2 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let mut gh = MockGH::new();
+        gh.expect_repository_metadata()
+            .with(eq("https://github.com/artifacthub/hub"))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-enrichment.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn opt_in_foundation_and_excluded_project() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: true,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                let project = &sync.projects_to_register[0];
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_register.len() == 1
+                    && project.name == "project"
+                    && project.repositories
+                        == vec![Repository {
+                            name: "docs".to_string(),
+                            url: "https://github.com/org/docs".to_string(),
+                            exclude: None,
+                            issues_filter_label: None,
+                            include: Some(vec!["clotributor".to_string()]),
+                            label_overrides: Some(BTreeMap::from([(
+                                "beginner".to_string(),
+                                "good first issue".to_string(),
+                            )])),
+                            docs_only: Some(true),
+                            github_node_id: None,
+                        }]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf-opt-in.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unregister_registered_project() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| {
+                let mut projects_registered = HashMap::new();
+                projects_registered.insert(
+                    "artifact-hub".to_string(),
+                    Some(
+                        "f7561fa613e158ed407cfff05cd47c091d14f106053a35a18a1b0563005010b1"
+                            .to_string(),
+                    ),
                 );
                 projects_registered.insert("project-name".to_string(), Some("digest".to_string()));
                 Box::pin(future::ready(Ok(projects_registered)))
             });
-        db.expect_unregister_project()
-            .with(eq(FOUNDATION), eq("project-name"))
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_register.is_empty()
+                    && sync.projects_to_unregister == vec!["project-name".to_string()]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .withf(|foundation_id, data_cache| {
+                foundation_id == FOUNDATION && data_cache.digest.is_some()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unnamed_invalid_entry_prevents_unregistration() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(registered_projects_to_unregister()))));
+        db.expect_sync_foundation().times(0);
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        // The last entry is not valid YAML and its name cannot be extracted
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf.yaml")).unwrap();
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body(format!("{data}- description: [Project description\n"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn project_failures_below_threshold_are_reported() {
+        let cfg = test_config_builder()
+            .set_override("registrar.maxFailedPercentage", 10)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_sync_foundation()
+            .withf(|sync| sync.projects_to_register.len() == 1 && sync.max_failures == 1)
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![ProjectFailure {
+                    project: "artifact-hub".to_string(),
+                    error: FAKE_ERROR.to_string(),
+                }])))
+            });
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION
+                    && run.processed == 1
+                    && run.errors.is_empty()
+                    && run.failures == vec![format!("artifact-hub: {FAKE_ERROR}")]
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn project_failures_threshold_exceeded() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(HashMap::new()))));
+        db.expect_sync_foundation()
+            .withf(|sync| sync.projects_to_register.len() == 1 && sync.max_failures == 0)
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![ProjectFailure {
+                    project: "artifact-hub".to_string(),
+                    error: FAKE_ERROR.to_string(),
+                }])))
+            });
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION
+                    && run.processed == 0
+                    && run.errors.len() == 1
+                    && run.failures.is_empty()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
+        assert_eq!(
+            result.unwrap_err().root_cause().to_string(),
+            format!(
+                "1 of 1 project changes failed, the maximum allowed is 0% (no changes were applied):\nartifact-hub: {FAKE_ERROR}"
+            )
+        );
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unregister_threshold_exceeded() {
+        let cfg = setup_test_config();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(registered_projects_to_unregister()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 0 && run.errors.len() == 1
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        let data_file_req = server
+            .mock("GET", "/")
+            .with_status(200)
+            .with_body_from_file(format!("{TESTDATA_PATH}/cncf.yaml"))
+            .create_async()
+            .await;
+
+        let result = run(&cfg, Arc::new(db), Arc::new(MockGH::new())).await;
+        assert_eq!(
+            result.unwrap_err().root_cause().to_string(),
+            "refusing to unregister 2 of 3 projects (66%), the maximum allowed is 50% (use --force-unregister to override)"
+        );
+        data_file_req.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unregister_threshold_exceeded_forced() {
+        let cfg = test_config_builder()
+            .set_override("registrar.forceUnregister", true)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let url = server.url();
+
+        let mut db = MockDB::new();
+        db.expect_foundations().times(1).returning(move || {
+            Box::pin(future::ready(Ok(vec![Foundation {
+                foundation_id: FOUNDATION.to_string(),
+                data_url: url.clone(),
+                data_format: DataFormat::Clomonitor,
+                data_cache: DataFileCache::default(),
+                opt_in: false,
+            }])))
+        });
+        db.expect_foundation_projects()
+            .with(eq(FOUNDATION))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(registered_projects_to_unregister()))));
+        db.expect_sync_foundation()
+            .withf(|sync| {
+                sync.foundation_id == FOUNDATION
+                    && sync.projects_to_unregister.len() == 2
+                    && sync
+                        .projects_to_unregister
+                        .iter()
+                        .all(|name| name.starts_with("project"))
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_purge_unregistered_projects()
+            .with(eq(FOUNDATION), eq(7))
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(0))));
+        db.expect_update_foundation_data_cache()
+            .withf(|foundation_id, data_cache| {
+                foundation_id == FOUNDATION && data_cache.digest.is_some()
+            })
+            .times(1)
+            .returning(|_, _| Box::pin(future::ready(Ok(()))));
+
+        db.expect_register_run()
+            .withf(|foundation_id, run| {
+                foundation_id == FOUNDATION && run.processed == 1 && run.errors.is_empty()
+            })
             .times(1)
             .returning(|_, _| Box::pin(future::ready(Ok(()))));
 
@@ -484,15 +2227,40 @@ mod tests {
             .create_async()
             .await;
 
-        run(&cfg, Arc::new(db)).await.unwrap();
+        run(&cfg, Arc::new(db), Arc::new(MockGH::new()))
+            .await
+            .unwrap();
         data_file_req.assert_async().await;
     }
 
+    fn registered_projects_to_unregister() -> HashMap<String, Option<String>> {
+        let mut projects_registered = HashMap::new();
+        projects_registered.insert(
+            "artifact-hub".to_string(),
+            Some("f7561fa613e158ed407cfff05cd47c091d14f106053a35a18a1b0563005010b1".to_string()),
+        );
+        projects_registered.insert("project1".to_string(), Some("digest".to_string()));
+        projects_registered.insert("project2".to_string(), Some("digest".to_string()));
+        projects_registered
+    }
+
     fn setup_test_config() -> Config {
+        test_config_builder().build().unwrap()
+    }
+
+    fn test_config_builder() -> ConfigBuilder<DefaultState> {
         Config::builder()
             .set_default("registrar.concurrency", 1)
             .unwrap()
-            .build()
+            .set_default("registrar.maxUnregisterPercentage", 50)
+            .unwrap()
+            .set_default("registrar.forceUnregister", false)
+            .unwrap()
+            .set_default("registrar.unregisterGracePeriodDays", 7)
+            .unwrap()
+            .set_default("registrar.maxFailedPercentage", 0)
+            .unwrap()
+            .set_default("registrar.enrichProjects", false)
             .unwrap()
     }
 }
@@ -0,0 +1,203 @@
+use std::{collections::HashSet, fmt, sync::LazyLock};
+
+use regex::Regex;
+use time::{Date, macros::format_description};
+
+use crate::{datasource::DataFileEntry, registrar::Project};
+
+static GITHUB_REPO_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("^https://github.com/[^/]+/[^/]+/?$").expect("exprs in GITHUB_REPO_URL to be valid")
+});
+
+static GITHUB_HANDLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("^[A-Za-z0-9]([A-Za-z0-9-]{0,37}[A-Za-z0-9])?$")
+        .expect("exprs in GITHUB_HANDLE to be valid")
+});
+
+static HTTP_URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^https?://\S+$").expect("exprs in HTTP_URL to be valid"));
+
+static VALID_MATURITY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new("^[a-z]+(-[a-z]+)*$").expect("exprs in VALID_MATURITY to be valid")
+});
+
+/// Problem found validating a project entry in a data file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ValidationError {
+    pub line: Option<usize>,
+    pub project: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        if let Some(project) = &self.project {
+            write!(f, "project {project}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validate the data file entries provided, returning the valid projects as
+/// well as the problems found in the invalid ones. Projects with problems are
+/// not included in the list of valid projects.
+pub(crate) fn validate(entries: Vec<DataFileEntry>) -> (Vec<Project>, Vec<ValidationError>) {
+    let mut projects = vec![];
+    let mut errors = vec![];
+    let mut names = HashSet::new();
+
+    for entry in entries {
+        let project = match entry.project {
+            Ok(project) => project,
+            Err(message) => {
+                errors.push(ValidationError {
+                    line: entry.line,
+                    project: entry.name,
+                    message,
+                });
+                continue;
+            }
+        };
+
+        let mut messages = validate_project(&project);
+        if !names.insert(project.name.clone()) {
+            messages.push("duplicate project name".to_string());
+        }
+        if messages.is_empty() {
+            projects.push(project);
+        } else {
+            errors.extend(messages.into_iter().map(|message| ValidationError {
+                line: entry.line,
+                project: Some(project.name.clone()),
+                message,
+            }));
+        }
+    }
+
+    (projects, errors)
+}
+
+/// Validate the project provided, returning the problems found.
+fn validate_project(project: &Project) -> Vec<String> {
+    let mut messages = vec![];
+
+    if project.name.trim().is_empty() {
+        messages.push("name cannot be empty".to_string());
+    }
+    if project.description.trim().is_empty() {
+        messages.push("description cannot be empty".to_string());
+    }
+    if let Some(maturity) = &project.maturity
+        && !VALID_MATURITY.is_match(maturity)
+    {
+        messages.push(format!(
+            "invalid maturity '{maturity}' (expected a lowercase word, e.g. sandbox)"
+        ));
+    }
+    if let Some(accepted_at) = &project.accepted_at
+        && Date::parse(accepted_at, format_description!("[year]-[month]-[day]")).is_err()
+    {
+        messages.push(format!(
+            "invalid accepted_at date '{accepted_at}' (expected format: YYYY-MM-DD)"
+        ));
+    }
+    for repository in &project.repositories {
+        if repository.name.trim().is_empty() {
+            messages.push(format!(
+                "repository name cannot be empty ({})",
+                repository.url
+            ));
+        }
+        if !GITHUB_REPO_URL.is_match(&repository.url) {
+            messages.push(format!(
+                "invalid repository url '{}' (expected format: https://github.com/owner/repo)",
+                repository.url
+            ));
+        }
+    }
+    for github_org in project.github_orgs.iter().flatten() {
+        if github_org.name.trim().is_empty() {
+            messages.push("github_org cannot be empty".to_string());
+        }
+    }
+    if let Some(maintainers_wanted) = &project.maintainers_wanted {
+        for link in maintainers_wanted.links.iter().flatten() {
+            if let Some(title) = &link.title
+                && title.trim().is_empty()
+            {
+                messages.push(format!(
+                    "maintainers_wanted link title cannot be empty ({})",
+                    link.url
+                ));
+            }
+            if !HTTP_URL.is_match(&link.url) {
+                messages.push(format!(
+                    "invalid maintainers_wanted link url '{}' (expected an http(s) url)",
+                    link.url
+                ));
+            }
+        }
+        let mut handles = HashSet::new();
+        for contact in maintainers_wanted.contacts.iter().flatten() {
+            if !GITHUB_HANDLE.is_match(&contact.github_handle) {
+                messages.push(format!(
+                    "invalid maintainers_wanted contact github_handle '{}' (expected a GitHub username, e.g. user1)",
+                    contact.github_handle
+                ));
+            } else if !handles.insert(contact.github_handle.to_lowercase()) {
+                messages.push(format!(
+                    "duplicate maintainers_wanted contact '{}'",
+                    contact.github_handle
+                ));
+            }
+        }
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::{DataFormat, parse_projects};
+
+    const TESTDATA_PATH: &str = "src/testdata";
+
+    #[test]
+    fn validate_valid_data_file() {
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf.yaml")).unwrap();
+        let entries = parse_projects(DataFormat::Clomonitor, "", &data).unwrap();
+
+        let (projects, errors) = validate(entries);
+        assert_eq!(projects.len(), 1);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn validate_invalid_data_file() {
+        let data = std::fs::read_to_string(format!("{TESTDATA_PATH}/cncf-invalid.yaml")).unwrap();
+        let entries = parse_projects(DataFormat::Clomonitor, "", &data).unwrap();
+
+        let (projects, errors) = validate(entries);
+        assert_eq!(
+            projects.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+            vec!["project1"]
+        );
+        assert_eq!(
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "line 8: project project2: missing field `description`",
+                "line 13: project project3: invalid maturity 'Sandbox Project' (expected a lowercase word, e.g. sandbox)",
+                "line 13: project project3: invalid accepted_at date '2022-13-01' (expected format: YYYY-MM-DD)",
+                "line 13: project project3: invalid repository url 'https://gitlab.com/org/repo3' (expected format: https://github.com/owner/repo)",
+                "line 22: project project1: duplicate project name",
+                "line 25: project project4: maintainers_wanted link title cannot be empty (contributing.md)",
+                "line 25: project project4: invalid maintainers_wanted link url 'contributing.md' (expected an http(s) url)",
+                "line 25: project project4: invalid maintainers_wanted contact github_handle '@user1' (expected a GitHub username, e.g. user1)",
+            ]
+        );
+    }
+}
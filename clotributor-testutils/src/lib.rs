@@ -0,0 +1,77 @@
+//! Utilities shared by the tests of the CLOTributor crates.
+
+use std::{
+    env, process,
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use deadpool_postgres::{Manager, Pool};
+use tokio_postgres::NoTls;
+
+/// Environment variable that holds the url of the tests database, which is
+/// used as a template for the database created for each test.
+const TESTS_DB_URL: &str = "CLOTRIBUTOR_TESTS_DB_URL";
+
+/// Id of the foundation used in the tests.
+pub const FOUNDATION: &str = "cncf";
+
+/// Setup a new database for a test, cloning the tests database (which must be
+/// already migrated), and return a pool connected to it.
+///
+/// # Panics
+///
+/// Panics if the tests database url is not set or the database cannot be
+/// created.
+pub async fn setup_test_db() -> Pool {
+    static DB_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    // Connect to the maintenance database
+    let url = env::var(TESTS_DB_URL).expect("tests database url to be set");
+    let mut pg_cfg = tokio_postgres::Config::from_str(&url).unwrap();
+    let template = pg_cfg.get_dbname().expect("dbname to be set").to_string();
+    pg_cfg.dbname("postgres");
+    let (client, conn) = pg_cfg.connect(NoTls).await.unwrap();
+    tokio::spawn(conn);
+
+    // Create test database (database level settings are not copied from the
+    // template, so they need to be set again)
+    let name = format!(
+        "{template}_{}_{}",
+        process::id(),
+        DB_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    client
+        .batch_execute(&format!("create database {name} template {template}"))
+        .await
+        .unwrap();
+    client
+        .batch_execute(&format!(
+            "alter database {name} set default_text_search_config = simple"
+        ))
+        .await
+        .unwrap();
+
+    // Setup pool connected to the test database
+    pg_cfg.dbname(&name);
+    Pool::builder(Manager::new(pg_cfg, NoTls)).build().unwrap()
+}
+
+/// Seed the database with the sample foundation used in the tests.
+///
+/// # Panics
+///
+/// Panics if the foundation cannot be registered.
+pub async fn seed_foundation(pool: &Pool) {
+    let client = pool.get().await.unwrap();
+    client
+        .execute(
+            "
+            insert into foundation (foundation_id, display_name, data_url)
+            values ($1::text, 'CNCF', 'https://data.url')
+            ",
+            &[&FOUNDATION],
+        )
+        .await
+        .unwrap();
+}
@@ -1,13 +1,20 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use deadpool_postgres::Pool;
 #[cfg(test)]
 use mockall::automock;
+use time::OffsetDateTime;
+use tokio_postgres::types::Json;
 use uuid::Uuid;
 
-use crate::tracker::{Issue, Repository};
+use crate::tracker::{
+    Issue, IssueArea, IssueDifficulty, IssueKind, IssueType, Repository, RepositorySync, Run,
+};
 
 /// Type alias to represent a DB trait object.
 pub(crate) type DynDB = Arc<dyn DB + Send + Sync>;
@@ -22,17 +29,16 @@ pub(crate) trait DB {
     /// Get repository's issues.
     async fn get_repository_issues(&self, repository_id: Uuid) -> Result<Vec<Issue>>;
 
-    /// Register issue provided in the database.
-    async fn register_issue(&self, repository: &Repository, issue: &Issue) -> Result<()>;
+    /// Mark issues similar enough to an older one in the same project as
+    /// duplicates of it.
+    async fn mark_duplicate_issues(&self, similarity_threshold: f64) -> Result<()>;
 
-    /// Unregister issue provided from the database.
-    async fn unregister_issue(&self, issue_id: i64) -> Result<()>;
+    /// Register run provided in the database.
+    async fn register_run(&self, run: &Run) -> Result<()>;
 
-    /// Update repository's GitHub data in the database.
-    async fn update_repository_gh_data(&self, repository: &Repository) -> Result<()>;
-
-    /// Update repository's last track timestamp.
-    async fn update_repository_last_track_ts(&self, repository_id: Uuid) -> Result<()>;
+    /// Apply the repository changes provided in the database in a single
+    /// transaction.
+    async fn sync_repository(&self, sync: &RepositorySync) -> Result<()>;
 }
 
 /// DB implementation backed by PostgreSQL.
@@ -59,18 +65,40 @@ impl DB for PgDB {
                     r.name,
                     r.description,
                     r.url,
+                    r.github_node_id,
                     r.homepage_url,
+                    r.contributing_url,
+                    r.code_of_conduct_url,
+                    r.license,
+                    r.default_branch,
+                    r.archived,
+                    r.disabled,
+                    r.last_commit_at,
+                    r.median_first_response_secs,
+                    r.open_pull_requests,
                     r.topics,
                     r.languages,
+                    r.language_sizes,
+                    r.language_shares,
+                    r.primary_language,
                     r.stars,
                     r.digest,
                     r.issues_filter_label,
+                    r.label_overrides,
+                    r.docs_only,
                     p.name as project_name,
+                    p.category as project_category,
+                    p.subcategory as project_subcategory,
                     p.foundation_id
                 from repository r
                 join project p using (project_id)
-                where r.tracked_at is null
-                or r.tracked_at < current_timestamp - '30 minutes'::interval
+                join foundation f using (foundation_id)
+                where f.enabled = true
+                and p.deleted_at is null
+                and (
+                    r.tracked_at is null
+                    or r.tracked_at < current_timestamp - '30 minutes'::interval
+                )
                 order by r.url asc;
                 ",
                 &[],
@@ -82,13 +110,36 @@ impl DB for PgDB {
                 name: row.get("name"),
                 description: row.get("description"),
                 url: row.get("url"),
+                github_node_id: row.get("github_node_id"),
                 homepage_url: row.get("homepage_url"),
+                contributing_url: row.get("contributing_url"),
+                code_of_conduct_url: row.get("code_of_conduct_url"),
+                license: row.get("license"),
+                default_branch: row.get("default_branch"),
+                archived: row.get("archived"),
+                disabled: row.get("disabled"),
+                last_commit_at: row.get("last_commit_at"),
+                median_first_response_secs: row.get("median_first_response_secs"),
+                open_pull_requests: row.get("open_pull_requests"),
                 topics: row.get("topics"),
                 languages: row.get("languages"),
+                language_sizes: row
+                    .get::<_, Option<Json<BTreeMap<String, i64>>>>("language_sizes")
+                    .map(|language_sizes| language_sizes.0),
+                language_shares: row
+                    .get::<_, Option<Json<BTreeMap<String, f64>>>>("language_shares")
+                    .map(|language_shares| language_shares.0),
+                primary_language: row.get("primary_language"),
                 stars: row.get("stars"),
                 digest: row.get("digest"),
                 issues_filter_label: row.get("issues_filter_label"),
+                label_overrides: row
+                    .get::<_, Option<Json<HashMap<String, String>>>>("label_overrides")
+                    .map(|label_overrides| label_overrides.0),
+                docs_only: row.get("docs_only"),
                 project_name: row.get("project_name"),
+                project_category: row.get("project_category"),
+                project_subcategory: row.get("project_subcategory"),
                 foundation_id: row.get("foundation_id"),
             })
             .collect();
@@ -108,13 +159,16 @@ impl DB for PgDB {
                     labels,
                     published_at,
                     has_linked_prs,
+                    type,
                     digest,
                     area,
                     kind,
                     difficulty,
+                    estimated_difficulty,
                     mentor_available,
                     mentor,
-                    good_first_issue
+                    good_first_issue,
+                    skills
                 from issue
                 where repository_id = $1;
                 ",
@@ -125,131 +179,836 @@ impl DB for PgDB {
             .map(|row| Issue {
                 issue_id: row.get("issue_id"),
                 title: row.get("title"),
-                url: row.get("title"),
+                url: row.get("url"),
                 number: row.get("number"),
                 labels: row.get("labels"),
                 published_at: row.get("published_at"),
                 has_linked_prs: row.get("has_linked_prs"),
+                issue_type: row.get("type"),
                 digest: row.get("digest"),
                 area: row.get("area"),
                 kind: row.get("kind"),
                 difficulty: row.get("difficulty"),
+                estimated_difficulty: row.get("estimated_difficulty"),
                 mentor_available: row.get("mentor_available"),
                 mentor: row.get("mentor"),
                 good_first_issue: row.get("good_first_issue"),
+                skills: row.get("skills"),
             })
             .collect();
         Ok(issues_ids)
     }
 
-    async fn register_issue(&self, repository: &Repository, issue: &Issue) -> Result<()> {
+    async fn mark_duplicate_issues(&self, similarity_threshold: f64) -> Result<()> {
         let db = self.pool.get().await?;
-        let ts_texts = issue.prepare_ts_texts(repository);
         db.execute(
-            "
-            insert into issue (
-                issue_id,
-                title,
-                url,
-                number,
-                labels,
-                digest,
-                area,
-                kind,
-                difficulty,
-                mentor_available,
-                mentor,
-                good_first_issue,
-                has_linked_prs,
-                published_at,
-                repository_id,
-                tsdoc
-            ) values (
-                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
-                setweight(to_tsvector($16), 'A') ||
-                setweight(to_tsvector($17), 'B') ||
-                setweight(to_tsvector($18), 'C')
-            ) on conflict (issue_id) do update
-            set
-                title = excluded.title,
-                labels = excluded.labels,
-                digest = excluded.digest,
-                area = excluded.area,
-                kind = excluded.kind,
-                difficulty = excluded.difficulty,
-                mentor_available = excluded.mentor_available,
-                mentor = excluded.mentor,
-                good_first_issue = excluded.good_first_issue,
-                has_linked_prs = excluded.has_linked_prs,
-                tsdoc = excluded.tsdoc;
-            ",
-            &[
-                &issue.issue_id,
-                &issue.title,
-                &issue.url,
-                &issue.number,
-                &issue.labels,
-                &issue.digest,
-                &issue.area,
-                &issue.kind,
-                &issue.difficulty,
-                &issue.mentor_available,
-                &issue.mentor,
-                &issue.good_first_issue,
-                &issue.has_linked_prs,
-                &issue.published_at,
-                &repository.repository_id,
-                &ts_texts.weight_a,
-                &ts_texts.weight_b,
-                &ts_texts.weight_c,
-            ],
+            "select mark_duplicate_issues($1::double precision)",
+            &[&similarity_threshold],
         )
         .await?;
         Ok(())
     }
 
-    async fn unregister_issue(&self, issue_id: i64) -> Result<()> {
+    async fn register_run(&self, run: &Run) -> Result<()> {
         let db = self.pool.get().await?;
-        db.execute("delete from issue where issue_id = $1;", &[&issue_id])
-            .await?;
+        db.execute(
+            "select register_run('tracker', null, $1::timestamptz, $2::integer, $3::text[], null)",
+            &[&run.started_at, &run.processed, &run.errors],
+        )
+        .await?;
         Ok(())
     }
 
-    async fn update_repository_gh_data(&self, repository: &Repository) -> Result<()> {
-        let db = self.pool.get().await?;
-        db.execute(
+    #[allow(clippy::too_many_lines)]
+    async fn sync_repository(&self, sync: &RepositorySync) -> Result<()> {
+        let mut db = self.pool.get().await?;
+        let tx = db.transaction().await?;
+        let repository = &sync.repository;
+
+        // Update repository's GitHub data if needed
+        if sync.gh_data_changed {
+            tx.execute(
+                "
+                update repository set
+                    description = $2,
+                    homepage_url = $3,
+                    languages = $4,
+                    stars = $5,
+                    topics = $6,
+                    digest = $7,
+                    github_node_id = $8,
+                    contributing_url = $9,
+                    code_of_conduct_url = $10,
+                    license = $11,
+                    default_branch = $12,
+                    archived = $13,
+                    last_commit_at = $14,
+                    median_first_response_secs = $15,
+                    open_pull_requests = $16,
+                    disabled = $17,
+                    language_sizes = $18,
+                    language_shares = $19,
+                    primary_language = $20,
+                    updated_at = current_timestamp
+                where repository_id = $1;
+                ",
+                &[
+                    &repository.repository_id,
+                    &repository.description,
+                    &repository.homepage_url,
+                    &repository.languages,
+                    &repository.stars,
+                    &repository.topics,
+                    &repository.digest,
+                    &repository.github_node_id,
+                    &repository.contributing_url,
+                    &repository.code_of_conduct_url,
+                    &repository.license,
+                    &repository.default_branch,
+                    &repository.archived,
+                    &repository.last_commit_at,
+                    &repository.median_first_response_secs,
+                    &repository.open_pull_requests,
+                    &repository.disabled,
+                    &repository.language_sizes.as_ref().map(Json),
+                    &repository.language_shares.as_ref().map(Json),
+                    &repository.primary_language,
+                ],
+            )
+            .await?;
+        }
+
+        // Register new or outdated issues
+        if !sync.issues_to_register.is_empty() {
+            let issues = IssuesColumns::new(repository, &sync.issues_to_register);
+            tx.execute(
+                "
+                insert into issue (
+                    issue_id,
+                    title,
+                    url,
+                    number,
+                    labels,
+                    digest,
+                    area,
+                    kind,
+                    difficulty,
+                    mentor_available,
+                    mentor,
+                    good_first_issue,
+                    skills,
+                    estimated_difficulty,
+                    has_linked_prs,
+                    type,
+                    published_at,
+                    repository_id,
+                    tsdoc
+                )
+                select
+                    i.issue_id,
+                    i.title,
+                    i.url,
+                    i.number,
+                    array(select jsonb_array_elements_text(i.labels)),
+                    i.digest,
+                    i.area,
+                    i.kind,
+                    i.difficulty,
+                    i.mentor_available,
+                    i.mentor,
+                    i.good_first_issue,
+                    array(select jsonb_array_elements_text(i.skills)),
+                    i.estimated_difficulty,
+                    i.has_linked_prs,
+                    i.type,
+                    i.published_at,
+                    $1::uuid,
+                    setweight(to_tsvector(i.weight_a), 'A') ||
+                    setweight(to_tsvector(i.weight_b), 'B') ||
+                    setweight(to_tsvector(i.weight_c), 'C')
+                from unnest(
+                    $2::bigint[],
+                    $3::text[],
+                    $4::text[],
+                    $5::integer[],
+                    $6::jsonb[],
+                    $7::text[],
+                    $8::area[],
+                    $9::kind[],
+                    $10::difficulty[],
+                    $11::boolean[],
+                    $12::text[],
+                    $13::boolean[],
+                    $14::boolean[],
+                    $15::issue_type[],
+                    $16::timestamptz[],
+                    $17::text[],
+                    $18::text[],
+                    $19::text[],
+                    $20::jsonb[],
+                    $21::difficulty[]
+                ) as i (
+                    issue_id,
+                    title,
+                    url,
+                    number,
+                    labels,
+                    digest,
+                    area,
+                    kind,
+                    difficulty,
+                    mentor_available,
+                    mentor,
+                    good_first_issue,
+                    has_linked_prs,
+                    type,
+                    published_at,
+                    weight_a,
+                    weight_b,
+                    weight_c,
+                    skills,
+                    estimated_difficulty
+                )
+                on conflict (issue_id, type) do update
+                set
+                    title = excluded.title,
+                    labels = excluded.labels,
+                    digest = excluded.digest,
+                    area = excluded.area,
+                    kind = excluded.kind,
+                    difficulty = excluded.difficulty,
+                    mentor_available = excluded.mentor_available,
+                    mentor = excluded.mentor,
+                    good_first_issue = excluded.good_first_issue,
+                    skills = excluded.skills,
+                    estimated_difficulty = excluded.estimated_difficulty,
+                    has_linked_prs = excluded.has_linked_prs,
+                    tsdoc = excluded.tsdoc;
+                ",
+                &[
+                    &repository.repository_id,
+                    &issues.issue_id,
+                    &issues.title,
+                    &issues.url,
+                    &issues.number,
+                    &issues.labels,
+                    &issues.digest,
+                    &issues.area,
+                    &issues.kind,
+                    &issues.difficulty,
+                    &issues.mentor_available,
+                    &issues.mentor,
+                    &issues.good_first_issue,
+                    &issues.has_linked_prs,
+                    &issues.issue_type,
+                    &issues.published_at,
+                    &issues.weight_a,
+                    &issues.weight_b,
+                    &issues.weight_c,
+                    &issues.skills,
+                    &issues.estimated_difficulty,
+                ],
+            )
+            .await?;
+        }
+
+        // Unregister issues no longer available
+        if !sync.issues_to_unregister.is_empty() {
+            let (issues_ids, issues_types): (Vec<i64>, Vec<IssueType>) =
+                sync.issues_to_unregister.iter().cloned().unzip();
+            tx.execute(
+                "
+                delete from issue
+                where (issue_id, type) in (
+                    select * from unnest($1::bigint[], $2::issue_type[])
+                );
+                ",
+                &[&issues_ids, &issues_types],
+            )
+            .await?;
+        }
+
+        // Update repository's last track timestamp (and flag it for duplicates
+        // marking when its issues changed)
+        let issues_changed =
+            !sync.issues_to_register.is_empty() || !sync.issues_to_unregister.is_empty();
+        tx.execute(
             "
             update repository set
-                description = $2,
-                homepage_url = $3,
-                languages = $4,
-                stars = $5,
-                topics = $6,
-                digest = $7,
-                updated_at = current_timestamp
+                tracked_at = current_timestamp,
+                issues_changed = issues_changed or $2
             where repository_id = $1;
             ",
-            &[
-                &repository.repository_id,
-                &repository.description,
-                &repository.homepage_url,
-                &repository.languages,
-                &repository.stars,
-                &repository.topics,
-                &repository.digest,
-            ],
+            &[&repository.repository_id, &issues_changed],
         )
         .await?;
+
+        tx.commit().await?;
         Ok(())
     }
+}
+
+/// Issues data arranged in columns, used to register multiple issues in the
+/// database using a single statement.
+#[derive(Default)]
+struct IssuesColumns {
+    issue_id: Vec<i64>,
+    title: Vec<String>,
+    url: Vec<String>,
+    number: Vec<i32>,
+    labels: Vec<Json<Vec<String>>>,
+    digest: Vec<Option<String>>,
+    area: Vec<Option<IssueArea>>,
+    kind: Vec<Option<IssueKind>>,
+    difficulty: Vec<Option<IssueDifficulty>>,
+    mentor_available: Vec<Option<bool>>,
+    mentor: Vec<Option<String>>,
+    good_first_issue: Vec<Option<bool>>,
+    has_linked_prs: Vec<bool>,
+    issue_type: Vec<IssueType>,
+    published_at: Vec<OffsetDateTime>,
+    weight_a: Vec<String>,
+    weight_b: Vec<String>,
+    weight_c: Vec<String>,
+    skills: Vec<Json<Vec<String>>>,
+    estimated_difficulty: Vec<Option<IssueDifficulty>>,
+}
+
+impl IssuesColumns {
+    /// Create a new IssuesColumns instance from the issues provided.
+    fn new(repository: &Repository, issues: &[Issue]) -> Self {
+        let mut columns = Self::default();
+        for issue in issues {
+            let ts_texts = issue.prepare_ts_texts(repository);
+            columns.issue_id.push(issue.issue_id);
+            columns.title.push(issue.title.clone());
+            columns.url.push(issue.url.clone());
+            columns.number.push(issue.number);
+            columns.labels.push(Json(issue.labels.clone()));
+            columns.digest.push(issue.digest.clone());
+            columns.area.push(issue.area.clone());
+            columns.kind.push(issue.kind.clone());
+            columns.difficulty.push(issue.difficulty.clone());
+            columns.mentor_available.push(issue.mentor_available);
+            columns.mentor.push(issue.mentor.clone());
+            columns.good_first_issue.push(issue.good_first_issue);
+            columns.has_linked_prs.push(issue.has_linked_prs);
+            columns.issue_type.push(issue.issue_type.clone());
+            columns.published_at.push(issue.published_at);
+            columns.weight_a.push(ts_texts.weight_a);
+            columns.weight_b.push(ts_texts.weight_b);
+            columns.weight_c.push(ts_texts.weight_c);
+            columns.skills.push(Json(issue.skills.clone()));
+            columns
+                .estimated_difficulty
+                .push(issue.estimated_difficulty.clone());
+        }
+        columns
+    }
+}
 
-    async fn update_repository_last_track_ts(&self, repository_id: Uuid) -> Result<()> {
-        let db = self.pool.get().await?;
-        db.execute(
-            "update repository set tracked_at = current_timestamp where repository_id = $1;",
-            &[&repository_id],
-        )
-        .await?;
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::format_description::well_known::Rfc3339;
+
+    const PROJECT_ID: &str = "00000000-0001-0000-0000-000000000000";
+    const REPOSITORY_ID: &str = "00000000-0000-0001-0000-000000000000";
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn get_repositories_to_track() {
+        let db = setup_test_db().await;
+        seed_repository(&db).await;
+
+        let repositories = db.get_repositories_to_track().await.unwrap();
+        assert_eq!(repositories, vec![sample_repository()]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    #[allow(clippy::too_many_lines)]
+    async fn sync_repository_register_issues() {
+        let db = setup_test_db().await;
+        seed_repository(&db).await;
+        let repository = Repository {
+            description: Some("new description".to_string()),
+            homepage_url: Some("https://new.homepage.url".to_string()),
+            contributing_url: Some("https://contributing.url".to_string()),
+            code_of_conduct_url: Some("https://coc.url".to_string()),
+            license: Some("Apache-2.0".to_string()),
+            default_branch: Some("main".to_string()),
+            archived: true,
+            disabled: true,
+            last_commit_at: Some(OffsetDateTime::parse("2023-01-02T03:04:05Z", &Rfc3339).unwrap()),
+            median_first_response_secs: Some(3600),
+            open_pull_requests: Some(5),
+            topics: Some(vec!["topic3".to_string()]),
+            languages: Some(vec!["language3".to_string()]),
+            language_sizes: Some(BTreeMap::from([("language3".to_string(), 1000)])),
+            language_shares: Some(BTreeMap::from([("language3".to_string(), 100.0)])),
+            primary_language: Some("language3".to_string()),
+            stars: Some(20),
+            digest: Some("new digest".to_string()),
+            github_node_id: Some("R_1".to_string()),
+            ..sample_repository()
+        };
+
+        db.sync_repository(&RepositorySync {
+            repository: repository.clone(),
+            gh_data_changed: true,
+            issues_to_register: vec![sample_issue(1), sample_issue(2)],
+            issues_to_unregister: vec![],
+        })
+        .await
+        .unwrap();
+
+        // Repository GitHub data and last track timestamp must have been updated
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "
+                select
+                    description,
+                    homepage_url,
+                    contributing_url,
+                    code_of_conduct_url,
+                    license,
+                    default_branch,
+                    archived,
+                    disabled,
+                    last_commit_at,
+                    median_first_response_secs,
+                    open_pull_requests,
+                    topics,
+                    languages,
+                    language_sizes,
+                    language_shares,
+                    primary_language,
+                    stars,
+                    digest,
+                    github_node_id,
+                    tracked_at is not null as tracked
+                from repository where repository_id = $1::uuid
+                ",
+                &[&repository.repository_id],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<_, Option<String>>("description"),
+            repository.description
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("homepage_url"),
+            repository.homepage_url
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("contributing_url"),
+            repository.contributing_url
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("code_of_conduct_url"),
+            repository.code_of_conduct_url
+        );
+        assert_eq!(row.get::<_, Option<String>>("license"), repository.license);
+        assert_eq!(
+            row.get::<_, Option<String>>("default_branch"),
+            repository.default_branch
+        );
+        assert_eq!(row.get::<_, bool>("archived"), repository.archived);
+        assert_eq!(row.get::<_, bool>("disabled"), repository.disabled);
+        assert_eq!(
+            row.get::<_, Option<OffsetDateTime>>("last_commit_at"),
+            repository.last_commit_at
+        );
+        assert_eq!(
+            row.get::<_, Option<i64>>("median_first_response_secs"),
+            repository.median_first_response_secs
+        );
+        assert_eq!(
+            row.get::<_, Option<i32>>("open_pull_requests"),
+            repository.open_pull_requests
+        );
+        assert_eq!(
+            row.get::<_, Option<Vec<String>>>("topics"),
+            repository.topics
+        );
+        assert_eq!(
+            row.get::<_, Option<Vec<String>>>("languages"),
+            repository.languages
+        );
+        assert_eq!(
+            row.get::<_, Option<Json<BTreeMap<String, i64>>>>("language_sizes")
+                .map(|language_sizes| language_sizes.0),
+            repository.language_sizes
+        );
+        assert_eq!(
+            row.get::<_, Option<Json<BTreeMap<String, f64>>>>("language_shares")
+                .map(|language_shares| language_shares.0),
+            repository.language_shares
+        );
+        assert_eq!(
+            row.get::<_, Option<String>>("primary_language"),
+            repository.primary_language
+        );
+        assert_eq!(row.get::<_, Option<i32>>("stars"), repository.stars);
+        assert_eq!(row.get::<_, Option<String>>("digest"), repository.digest);
+        assert_eq!(
+            row.get::<_, Option<String>>("github_node_id"),
+            repository.github_node_id
+        );
+        assert!(row.get::<_, bool>("tracked"));
+        assert!(db.get_repositories_to_track().await.unwrap().is_empty());
+
+        // All issues fields must round-trip
+        let mut issues = db
+            .get_repository_issues(repository.repository_id)
+            .await
+            .unwrap();
+        issues.sort_by_key(|issue| issue.issue_id);
+        assert_eq!(issues, vec![sample_issue(1), sample_issue(2)]);
+
+        // Issues text search document must have been built
+        let row = client
+            .query_one("select tsdoc::text from issue where issue_id = 1", &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<_, String>("tsdoc"),
+            "'cncf':2B 'description':5B 'issue1':8C 'label1':9C 'label2':10C \
+            'language3':7B 'new':4B 'project':1A 'repo':3B 'topic3':6B"
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn sync_repository_update_and_unregister_issues() {
+        let db = setup_test_db().await;
+        seed_repository(&db).await;
+        let repository = sample_repository();
+        db.sync_repository(&RepositorySync {
+            repository: repository.clone(),
+            gh_data_changed: false,
+            issues_to_register: vec![sample_issue(1), sample_issue(2)],
+            issues_to_unregister: vec![],
+        })
+        .await
+        .unwrap();
+
+        let updated_issue = Issue {
+            title: "updated title".to_string(),
+            labels: vec!["label3".to_string()],
+            has_linked_prs: false,
+            digest: Some("updated digest".to_string()),
+            area: None,
+            kind: None,
+            difficulty: None,
+            estimated_difficulty: None,
+            mentor_available: None,
+            mentor: None,
+            good_first_issue: None,
+            ..sample_issue(1)
+        };
+        db.sync_repository(&RepositorySync {
+            repository: repository.clone(),
+            gh_data_changed: false,
+            issues_to_register: vec![updated_issue.clone()],
+            issues_to_unregister: vec![(2, IssueType::Issue)],
+        })
+        .await
+        .unwrap();
+
+        let issues = db
+            .get_repository_issues(repository.repository_id)
+            .await
+            .unwrap();
+        assert_eq!(issues, vec![updated_issue]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn sync_repository_issue_and_pull_request_same_id() {
+        let db = setup_test_db().await;
+        seed_repository(&db).await;
+        let repository = sample_repository();
+        let pull_request = Issue {
+            issue_type: IssueType::PullRequest,
+            has_linked_prs: false,
+            ..sample_issue(1)
+        };
+        db.sync_repository(&RepositorySync {
+            repository: repository.clone(),
+            gh_data_changed: false,
+            issues_to_register: vec![sample_issue(1), pull_request.clone()],
+            issues_to_unregister: vec![],
+        })
+        .await
+        .unwrap();
+
+        db.sync_repository(&RepositorySync {
+            repository: repository.clone(),
+            gh_data_changed: false,
+            issues_to_register: vec![],
+            issues_to_unregister: vec![(1, IssueType::Issue)],
+        })
+        .await
+        .unwrap();
+
+        let issues = db
+            .get_repository_issues(repository.repository_id)
+            .await
+            .unwrap();
+        assert_eq!(issues, vec![pull_request]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    #[allow(clippy::too_many_lines)]
+    async fn mark_duplicate_issues() {
+        let db = setup_test_db().await;
+        seed_repository(&db).await;
+        let issue = |issue_id: i64, title: &str| Issue {
+            title: title.to_string(),
+            ..sample_issue(issue_id)
+        };
+        db.sync_repository(&RepositorySync {
+            repository: sample_repository(),
+            gh_data_changed: false,
+            issues_to_register: vec![
+                issue(1, "Add support for ARM64 builds"),
+                issue(2, "[build] Add support for arm64 builds!"),
+                issue(3, "Fix typo in README"),
+                issue(4, "Add unit tests"),
+                issue(5, "Add unit tests."),
+                issue(6, "Add support for ARM64 builds in the operator"),
+                issue(7, "Add support for ARM64 builds in the operator Helm chart"),
+                issue(
+                    8,
+                    "Add support for ARM64 builds in the operator Helm chart images",
+                ),
+            ],
+            issues_to_unregister: vec![],
+        })
+        .await
+        .unwrap();
+        let client = db.pool.get().await.unwrap();
+        let canonical_issues = || async {
+            let rows = client
+                .query(
+                    "select issue_id, canonical_issue_id from issue order by issue_id asc",
+                    &[],
+                )
+                .await
+                .unwrap();
+            rows.iter()
+                .map(|row| (row.get(0), row.get(1)))
+                .collect::<Vec<(i64, Option<i64>)>>()
+        };
+
+        // Issues with short titles are never considered duplicates, and chains
+        // of similar issues are resolved to the first one (8 is similar to 7
+        // but not to 6, which 7 is a duplicate of)
+        db.mark_duplicate_issues(0.8).await.unwrap();
+        assert_eq!(
+            canonical_issues().await,
+            vec![
+                (1, None),
+                (2, Some(1)),
+                (3, None),
+                (4, None),
+                (5, None),
+                (6, None),
+                (7, Some(6)),
+                (8, Some(6)),
+            ]
+        );
+
+        // Projects whose issues haven't changed since the last time are not
+        // processed again
+        client
+            .execute("update issue set canonical_issue_id = null", &[])
+            .await
+            .unwrap();
+        db.mark_duplicate_issues(0.8).await.unwrap();
+        assert_eq!(
+            canonical_issues().await,
+            vec![
+                (1, None),
+                (2, None),
+                (3, None),
+                (4, None),
+                (5, None),
+                (6, None),
+                (7, None),
+                (8, None),
+            ]
+        );
+        db.sync_repository(&RepositorySync {
+            repository: sample_repository(),
+            gh_data_changed: false,
+            issues_to_register: vec![],
+            issues_to_unregister: vec![(5, IssueType::Issue)],
+        })
+        .await
+        .unwrap();
+        db.mark_duplicate_issues(0.8).await.unwrap();
+        assert_eq!(
+            canonical_issues().await,
+            vec![
+                (1, None),
+                (2, Some(1)),
+                (3, None),
+                (4, None),
+                (6, None),
+                (7, Some(6)),
+                (8, Some(6)),
+            ]
+        );
+
+        // The similarity threshold is not changed for the connection
+        client
+            .execute("select mark_duplicate_issues(0.8)", &[])
+            .await
+            .unwrap();
+        let threshold: f32 = client
+            .query_one("select show_limit()", &[])
+            .await
+            .unwrap()
+            .get(0);
+        assert!((threshold - 0.3).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a database (scripts/db-tests.sh)"]
+    async fn register_run() {
+        let db = setup_test_db().await;
+        let run = Run {
+            started_at: OffsetDateTime::parse("2023-01-02T03:04:05.123456Z", &Rfc3339).unwrap(),
+            processed: 2,
+            errors: vec!["error1".to_string()],
+        };
+
+        db.register_run(&run).await.unwrap();
+
+        let client = db.pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "select kind, status, processed, errors, started_at, foundation_id from run",
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>("kind"), "tracker");
+        assert_eq!(row.get::<_, String>("status"), "failed");
+        assert_eq!(row.get::<_, i32>("processed"), run.processed);
+        assert_eq!(
+            row.get::<_, Option<Vec<String>>>("errors"),
+            Some(run.errors)
+        );
+        assert_eq!(row.get::<_, OffsetDateTime>("started_at"), run.started_at);
+        assert_eq!(row.get::<_, Option<String>>("foundation_id"), None);
+    }
+
+    /// Setup a new database for a test (see clotributor_testutils).
+    async fn setup_test_db() -> PgDB {
+        PgDB::new(clotributor_testutils::setup_test_db().await)
+    }
+
+    /// Seed the database with the sample repository (and its project).
+    async fn seed_repository(db: &PgDB) {
+        clotributor_testutils::seed_foundation(&db.pool).await;
+        let client = db.pool.get().await.unwrap();
+        client
+            .batch_execute(&format!(
+                "
+                insert into project (project_id, name, foundation_id)
+                values ('{PROJECT_ID}', 'project', 'cncf');
+
+                insert into repository (
+                    repository_id,
+                    name,
+                    description,
+                    url,
+                    homepage_url,
+                    topics,
+                    languages,
+                    stars,
+                    digest,
+                    issues_filter_label,
+                    project_id
+                ) values (
+                    '{REPOSITORY_ID}',
+                    'repo',
+                    'description',
+                    'https://github.com/org/repo1',
+                    'https://homepage.url',
+                    '{{topic1,topic2}}',
+                    '{{language1,language2}}',
+                    10,
+                    'digest',
+                    'label',
+                    '{PROJECT_ID}'
+                );
+                "
+            ))
+            .await
+            .unwrap();
+    }
+
+    fn sample_repository() -> Repository {
+        Repository {
+            repository_id: Uuid::parse_str(REPOSITORY_ID).unwrap(),
+            name: "repo".to_string(),
+            description: Some("description".to_string()),
+            url: "https://github.com/org/repo1".to_string(),
+            github_node_id: None,
+            homepage_url: Some("https://homepage.url".to_string()),
+            contributing_url: None,
+            code_of_conduct_url: None,
+            license: None,
+            default_branch: None,
+            archived: false,
+            disabled: false,
+            last_commit_at: None,
+            median_first_response_secs: None,
+            open_pull_requests: None,
+            topics: Some(vec!["topic1".to_string(), "topic2".to_string()]),
+            languages: Some(vec!["language1".to_string(), "language2".to_string()]),
+            language_sizes: None,
+            language_shares: None,
+            primary_language: None,
+            stars: Some(10),
+            digest: Some("digest".to_string()),
+            issues_filter_label: Some("label".to_string()),
+            label_overrides: None,
+            docs_only: false,
+            project_name: "project".to_string(),
+            project_category: None,
+            project_subcategory: None,
+            foundation_id: "cncf".to_string(),
+        }
+    }
+
+    fn sample_issue(issue_id: i64) -> Issue {
+        Issue {
+            issue_id,
+            title: format!("issue{issue_id}"),
+            url: format!("https://github.com/org/repo1/issues/{issue_id}"),
+            number: i32::try_from(issue_id).unwrap(),
+            labels: vec!["label1".to_string(), "label2".to_string()],
+            published_at: OffsetDateTime::parse("2023-01-02T03:04:05.123456Z", &Rfc3339).unwrap(),
+            has_linked_prs: true,
+            issue_type: IssueType::Issue,
+            digest: Some(format!("digest{issue_id}")),
+            area: Some(IssueArea::Docs),
+            kind: Some(IssueKind::Bug),
+            difficulty: Some(IssueDifficulty::Easy),
+            estimated_difficulty: None,
+            mentor_available: Some(true),
+            mentor: Some("mentor".to_string()),
+            good_first_issue: Some(true),
+            skills: vec!["Go".to_string()],
+        }
     }
 }
@@ -1,4 +1,7 @@
-use std::sync::{Arc, LazyLock};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, LazyLock},
+};
 
 use anyhow::{Context, Result, bail, format_err};
 use async_trait::async_trait;
@@ -12,13 +15,19 @@ use time::{
     ext::NumericalDuration,
     format_description::well_known::{Iso8601, Rfc3339},
 };
+use tracing::debug;
 
-use crate::tracker::Issue;
+use crate::tracker::{Issue, IssueType};
 
 /// GitHub GraphQL API URL.
 const GITHUB_GRAPHQL_API_URL: &str = "https://api.github.com/graphql";
 
-/// Label used to filter the issues we want to track.
+/// Maximum number of pages of discussions (100 per page, most recent first)
+/// requested per repository. Discussions are filtered by label and category
+/// once fetched, so the number of requests per repository must be bounded.
+const MAX_DISCUSSIONS_PAGES: usize = 2;
+
+/// Label used to filter the issues and pull requests we want to track.
 const DEFAULT_ISSUES_FILTER_LABEL: &str = "help wanted";
 
 static GITHUB_REPO_URL: LazyLock<Regex> = LazyLock::new(|| {
@@ -33,6 +42,9 @@ pub(crate) type DynGH = Arc<dyn GH + Send + Sync>;
 #[allow(clippy::upper_case_acronyms)]
 type URI = String;
 
+/// Type alias for GraphQL BigInt scalar type.
+type BigInt = String;
+
 /// Type alias for GraphQL DateTime scalar type.
 type DateTime = String;
 
@@ -45,6 +57,15 @@ type DateTime = String;
 )]
 pub struct RepoView;
 
+/// GitHub repository pull requests view (represents GitHub GraphQL API query).
+#[derive(Debug, Clone, GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/github_schema.graphql",
+    query_path = "src/graphql/pull_requests_view.graphql",
+    response_derives = "Debug, PartialEq, Eq"
+)]
+pub struct PullRequestsView;
+
 impl repo_view::RepoViewRepository {
     /// Return repository issues.
     pub(crate) fn issues(&self) -> Vec<Issue> {
@@ -99,15 +120,20 @@ impl repo_view::RepoViewRepository {
                             labels,
                             published_at,
                             has_linked_prs,
+                            issue_type: IssueType::Issue,
                             digest: None,
                             area: None,
                             kind: None,
                             difficulty: None,
+                            estimated_difficulty: None,
                             mentor_available: None,
                             mentor: None,
                             good_first_issue: None,
+                            skills: vec![],
                         };
                         issue.populate_from_labels();
+                        issue.populate_skills_from_text(&node.body);
+                        issue.estimate_difficulty(Some(&node.body));
                         issue.update_digest();
 
                         Some(issue)
@@ -116,6 +142,260 @@ impl repo_view::RepoViewRepository {
             })
             .unwrap_or_default()
     }
+
+    /// Return the date of the last commit in the repository's default branch.
+    pub(crate) fn last_commit_at(&self) -> Option<OffsetDateTime> {
+        let target = self.default_branch_ref.as_ref()?.target.as_ref()?;
+        match target {
+            repo_view::RepoViewRepositoryDefaultBranchRefTarget::Commit(commit) => {
+                OffsetDateTime::parse(&commit.committed_date, &Rfc3339).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the size (in bytes) of the code written in each of the
+    /// repository's main languages, ordered by size (largest first).
+    pub(crate) fn language_sizes(&self) -> Option<Vec<(String, i64)>> {
+        self.languages.as_ref()?.edges.as_ref().map(|edges| {
+            edges
+                .iter()
+                .flatten()
+                .map(|edge| (edge.node.name.clone(), edge.size))
+                .collect()
+        })
+    }
+
+    /// Return the share (percentage of the total size of the code in the
+    /// repository, rounded to two decimals) of each of the repository's main
+    /// languages.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn language_shares(&self) -> Option<BTreeMap<String, f64>> {
+        let total_size = self.languages.as_ref()?.total_size;
+        if total_size <= 0 {
+            return None;
+        }
+        let shares = self
+            .language_sizes()?
+            .into_iter()
+            .map(|(name, size)| {
+                let share = (size as f64 * 100.0 / total_size as f64 * 100.0).round() / 100.0;
+                (name, share)
+            })
+            .collect();
+        Some(shares)
+    }
+
+    /// Return the median time (in seconds) it took for someone other than the
+    /// author to respond to the repository's most recent issues. Comments
+    /// from bots are ignored, and issues that haven't received a response yet
+    /// are not taken into account.
+    pub(crate) fn median_first_response_secs(&self) -> Option<i64> {
+        let mut response_times: Vec<i64> = self
+            .recent_issues
+            .nodes
+            .as_ref()?
+            .iter()
+            .flatten()
+            .filter_map(|issue| {
+                let author = issue.author.as_ref().map(|author| &author.login);
+                let created_at = OffsetDateTime::parse(&issue.created_at, &Rfc3339).ok()?;
+                let first_response_at = issue
+                    .comments
+                    .nodes
+                    .as_ref()?
+                    .iter()
+                    .flatten()
+                    .find(|comment| {
+                        let Some(comment_author) = &comment.author else {
+                            return true;
+                        };
+                        !matches!(
+                            comment_author.on,
+                            repo_view::RepoViewRepositoryRecentIssuesNodesCommentsNodesAuthorOn::Bot
+                        ) && Some(&comment_author.login) != author
+                    })
+                    .and_then(|comment| {
+                        OffsetDateTime::parse(&comment.created_at, &Rfc3339).ok()
+                    })?;
+                Some((first_response_at - created_at).whole_seconds())
+            })
+            .collect();
+        if response_times.is_empty() {
+            return None;
+        }
+        response_times.sort_unstable();
+        let middle = response_times.len() / 2;
+        if response_times.len().is_multiple_of(2) {
+            Some(i64::midpoint(
+                response_times[middle - 1],
+                response_times[middle],
+            ))
+        } else {
+            Some(response_times[middle])
+        }
+    }
+}
+
+impl pull_requests_view::PullRequestsViewRepository {
+    /// Return repository pull requests.
+    pub(crate) fn pull_requests(&self) -> Vec<Issue> {
+        self.pull_requests
+            .nodes
+            .as_ref()
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .flatten()
+                    .filter_map(|node| {
+                        // Check if required fields are present
+                        let pull_request_id = node
+                            .full_database_id
+                            .as_ref()
+                            .and_then(|id| id.parse::<i64>().ok())?;
+                        let published_at = node.published_at.as_ref()?;
+
+                        // Prepare labels
+                        let labels = node
+                            .labels
+                            .as_ref()
+                            .and_then(|labels| labels.nodes.as_ref())
+                            .map(|nodes| {
+                                nodes
+                                    .iter()
+                                    .flatten()
+                                    .map(|node| node.name.clone())
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+
+                        // Prepare published date
+                        let published_at = OffsetDateTime::parse(published_at, &Rfc3339)
+                            .expect("date to be valid");
+
+                        // Prepare pull request
+                        #[allow(clippy::cast_possible_truncation)]
+                        let mut pull_request = Issue {
+                            issue_id: pull_request_id,
+                            title: node.title.clone(),
+                            url: node.url.clone(),
+                            number: node.number as i32,
+                            labels,
+                            published_at,
+                            has_linked_prs: false,
+                            issue_type: IssueType::PullRequest,
+                            digest: None,
+                            area: None,
+                            kind: None,
+                            difficulty: None,
+                            estimated_difficulty: None,
+                            mentor_available: None,
+                            mentor: None,
+                            good_first_issue: None,
+                            skills: vec![],
+                        };
+                        pull_request.populate_from_labels();
+                        pull_request.estimate_difficulty(None);
+                        pull_request.update_digest();
+
+                        Some(pull_request)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// GitHub repository discussions view (represents GitHub GraphQL API query).
+#[derive(Debug, Clone, GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/github_schema.graphql",
+    query_path = "src/graphql/discussions_view.graphql",
+    response_derives = "Debug, PartialEq, Eq"
+)]
+pub struct DiscussionsView;
+
+impl discussions_view::DiscussionsViewRepository {
+    /// Return repository discussions that contain the filter label provided
+    /// and belong to any of the categories provided (when no categories are
+    /// provided, discussions in any category will be returned).
+    pub(crate) fn discussions(
+        &self,
+        discussions_filter_label: Option<&String>,
+        categories: &[String],
+    ) -> Vec<Issue> {
+        let filter_label =
+            discussions_filter_label.map_or(DEFAULT_ISSUES_FILTER_LABEL, String::as_str);
+
+        self.discussions
+            .nodes
+            .as_ref()
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .flatten()
+                    .filter_map(|node| {
+                        // Check if required fields are present
+                        let discussion_id = node.database_id?;
+                        let published_at = node.published_at.as_ref()?;
+
+                        // Check if the discussion belongs to the categories
+                        if !categories.is_empty() && !categories.contains(&node.category.name) {
+                            return None;
+                        }
+
+                        // Prepare labels and check the filter label is present
+                        let labels: Vec<String> = node
+                            .labels
+                            .as_ref()
+                            .and_then(|labels| labels.nodes.as_ref())
+                            .map(|nodes| {
+                                nodes
+                                    .iter()
+                                    .flatten()
+                                    .map(|node| node.name.clone())
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        if !labels.iter().any(|label| label == filter_label) {
+                            return None;
+                        }
+
+                        // Prepare published date
+                        let published_at = OffsetDateTime::parse(published_at, &Rfc3339)
+                            .expect("date to be valid");
+
+                        // Prepare discussion
+                        #[allow(clippy::cast_possible_truncation)]
+                        let mut discussion = Issue {
+                            issue_id: discussion_id,
+                            title: node.title.clone(),
+                            url: node.url.clone(),
+                            number: node.number as i32,
+                            labels,
+                            published_at,
+                            has_linked_prs: false,
+                            issue_type: IssueType::Discussion,
+                            digest: None,
+                            area: None,
+                            kind: None,
+                            difficulty: None,
+                            estimated_difficulty: None,
+                            mentor_available: None,
+                            mentor: None,
+                            good_first_issue: None,
+                            skills: vec![],
+                        };
+                        discussion.populate_from_labels();
+                        discussion.estimate_difficulty(None);
+                        discussion.update_digest();
+
+                        Some(discussion)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 /// Trait that defines some operations a GH implementation must support.
@@ -130,6 +410,22 @@ pub(crate) trait GH {
         url: &str,
         issues_filter_label: Option<&String>,
     ) -> Result<repo_view::RepoViewRepository>;
+
+    /// Get repository's open pull requests from GitHub.
+    async fn pull_requests(
+        &self,
+        token: &str,
+        url: &str,
+        pull_requests_filter_label: Option<&String>,
+    ) -> Result<pull_requests_view::PullRequestsViewRepository>;
+
+    /// Get repository's most recent open discussions from GitHub (up to
+    /// `MAX_DISCUSSIONS_PAGES` pages).
+    async fn discussions(
+        &self,
+        token: &str,
+        url: &str,
+    ) -> Result<discussions_view::DiscussionsViewRepository>;
 }
 
 /// GH implementation backed by the GitHub GraphQL API.
@@ -150,8 +446,6 @@ impl GH for GHGraphQL {
         url: &str,
         issues_filter_label: Option<&String>,
     ) -> Result<repo_view::RepoViewRepository> {
-        // Do request to GraphQL API
-        let http_client = setup_http_client(token)?;
         let (owner, repo) = get_owner_and_repo(url)?;
         let issues_since = OffsetDateTime::now_utc()
             .saturating_sub(365.days())
@@ -165,32 +459,110 @@ impl GH for GHGraphQL {
             issues_label,
             issues_since,
         };
-        let req_body = &RepoView::build_query(vars);
-        let resp = http_client
-            .post(GITHUB_GRAPHQL_API_URL)
-            .json(req_body)
-            .send()
-            .await
-            .context("error querying graphql api")?;
-        if resp.status() != StatusCode::OK {
-            bail!(
-                "unexpected status code querying graphql api: {} - {}",
-                resp.status(),
-                resp.text().await?,
-            );
-        }
+        query_graphql_api::<RepoView>(token, vars)
+            .await?
+            .repository
+            .ok_or_else(|| format_err!("repository field not found"))
+    }
 
-        // Parse response body and extract repository data
-        let resp_body = resp.text().await?;
-        let repo = serde_json::from_str::<Response<repo_view::ResponseData>>(&resp_body)
-            .context(format!("error deserializing query response: {resp_body}"))?
-            .data
-            .ok_or_else(|| format_err!("data field not found: {resp_body}"))?
+    async fn pull_requests(
+        &self,
+        token: &str,
+        url: &str,
+        pull_requests_filter_label: Option<&String>,
+    ) -> Result<pull_requests_view::PullRequestsViewRepository> {
+        let (owner, repo) = get_owner_and_repo(url)?;
+        let pull_requests_label = pull_requests_filter_label
+            .cloned()
+            .unwrap_or(DEFAULT_ISSUES_FILTER_LABEL.to_string());
+        let vars = pull_requests_view::Variables {
+            repo,
+            owner,
+            pull_requests_label,
+        };
+        query_graphql_api::<PullRequestsView>(token, vars)
+            .await?
             .repository
-            .ok_or_else(|| format_err!("repository field not found: {resp_body}"))?;
+            .ok_or_else(|| format_err!("repository field not found"))
+    }
+
+    async fn discussions(
+        &self,
+        token: &str,
+        url: &str,
+    ) -> Result<discussions_view::DiscussionsViewRepository> {
+        let (owner, repo) = get_owner_and_repo(url)?;
+        let mut repository: Option<discussions_view::DiscussionsViewRepository> = None;
+        let mut after = None;
+        let mut pages = 0;
+        loop {
+            let vars = discussions_view::Variables {
+                repo: repo.clone(),
+                owner: owner.clone(),
+                after,
+            };
+            let page = query_graphql_api::<DiscussionsView>(token, vars)
+                .await?
+                .repository
+                .ok_or_else(|| format_err!("repository field not found"))?;
+            let has_next_page = page.discussions.page_info.has_next_page;
+            let end_cursor = page.discussions.page_info.end_cursor.clone();
+
+            // Collect the discussions of all pages in the first one
+            match repository.as_mut() {
+                None => repository = Some(page),
+                Some(repository) => repository
+                    .discussions
+                    .nodes
+                    .get_or_insert_with(Vec::new)
+                    .extend(page.discussions.nodes.unwrap_or_default()),
+            }
+
+            if !has_next_page || end_cursor.is_none() {
+                break;
+            }
+            pages += 1;
+            if pages == MAX_DISCUSSIONS_PAGES {
+                debug!(url, "discussions pages limit reached");
+                break;
+            }
+            after = end_cursor;
+        }
+        repository.ok_or_else(|| format_err!("repository field not found"))
+    }
+}
 
-        Ok(repo)
+/// Do a request to the GitHub GraphQL API using the query and variables
+/// provided, returning the response data.
+async fn query_graphql_api<Q: GraphQLQuery>(
+    token: &str,
+    vars: Q::Variables,
+) -> Result<Q::ResponseData> {
+    // Do request to GraphQL API
+    let http_client = setup_http_client(token)?;
+    let req_body = &Q::build_query(vars);
+    let resp = http_client
+        .post(GITHUB_GRAPHQL_API_URL)
+        .json(req_body)
+        .send()
+        .await
+        .context("error querying graphql api")?;
+    if resp.status() != StatusCode::OK {
+        bail!(
+            "unexpected status code querying graphql api: {} - {}",
+            resp.status(),
+            resp.text().await?,
+        );
     }
+
+    // Parse response body and extract data
+    let resp_body = resp.text().await?;
+    let data = serde_json::from_str::<Response<Q::ResponseData>>(&resp_body)
+        .context(format!("error deserializing query response: {resp_body}"))?
+        .data
+        .ok_or_else(|| format_err!("data field not found: {resp_body}"))?;
+
+    Ok(data)
 }
 
 // Setup a new authenticated http client to interact with the GitHub API.
@@ -33,6 +33,10 @@ async fn main() -> Result<()> {
     // Setup configuration
     let cfg = Config::builder()
         .set_default("tracker.concurrency", 10)?
+        .set_default("tracker.trackPullRequests", false)?
+        .set_default("tracker.trackDiscussions", false)?
+        .set_default("tracker.discussionsCategories", Vec::<String>::new())?
+        .set_default("tracker.duplicatesSimilarityThreshold", 0.8)?
         .add_source(File::from(args.config))
         .build()
         .context("error setting up configuration")?;
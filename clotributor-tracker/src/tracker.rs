@@ -1,17 +1,22 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
 
 use anyhow::{Context, Error, Result, bail, format_err};
 use config::Config;
 use deadpool::unmanaged::{Object, Pool};
 use futures::stream::{self, StreamExt};
 use postgres_types::{FromSql, ToSql};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 #[cfg(not(test))]
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 use tokio::time::timeout;
-use tracing::{debug, info, instrument};
+use tracing::{debug, error, info, instrument};
 use uuid::Uuid;
 
 #[cfg(not(test))]
@@ -24,6 +29,56 @@ use crate::{
 /// Maximum time that can take tracking a single repository.
 const REPOSITORY_TRACK_TIMEOUT: u64 = 300;
 
+/// Languages that can be detected as issue skills, along with the aliases
+/// used to refer to them in labels and the extensions of their files.
+const SKILLS_LANGUAGES: &[(&str, &[&str], &[&str])] = &[
+    ("C", &["c"], &["c", "h"]),
+    ("C#", &["c#", "csharp"], &["cs"]),
+    ("C++", &["c++", "cpp"], &["cc", "cpp", "hpp"]),
+    ("CSS", &["css", "scss"], &["css", "scss"]),
+    ("Go", &["go", "golang"], &["go"]),
+    ("HTML", &["html"], &["html"]),
+    ("Java", &["java"], &["java"]),
+    ("JavaScript", &["javascript", "js"], &["js", "jsx", "mjs"]),
+    ("Kotlin", &["kotlin"], &["kt"]),
+    ("PHP", &["php"], &["php"]),
+    ("Python", &["python"], &["py"]),
+    ("Ruby", &["ruby"], &["rb"]),
+    ("Rust", &["rust"], &["rs"]),
+    ("Shell", &["shell", "bash"], &["bash", "sh"]),
+    ("TypeScript", &["typescript", "ts"], &["ts", "tsx"]),
+];
+
+/// Prefixes of the labels used to describe the skills an issue requires. The
+/// skills extracted from them are named like languages are (e.g. using a
+/// display name like `Networking` or `Machine Learning`).
+const SKILLS_LABELS_PREFIXES: &[&str] = &["lang/", "language/", "area/", "skill/"];
+
+/// Regular expression used to find file paths in the issues' body.
+static FILE_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:[\w.-]+/)*[\w-]+\.(?P<extension>[A-Za-z]+)\b")
+        .expect("exprs in FILE_PATH to be valid")
+});
+
+/// Regular expression used to find urls in the issues' body. The path of the
+/// files linked in GitHub repositories is captured, as it's a file mentioned.
+static URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?:https?://|www\.)(?:github\.com/[^/\s]+/[^/\s]+/(?:blob|tree)/[^/\s]+/(?P<path>[^\s)\]>`#?]+)[^\s)\]>`]*|[^\s)\]>`]+)",
+    )
+    .expect("exprs in URL to be valid")
+});
+
+/// Regular expression used to find code spans and fenced code blocks in the
+/// issues' body.
+static CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)```.*?```|`[^`\n]+`").expect("exprs in CODE to be valid"));
+
+/// Regular expression used to find checklist items in the issues' body.
+static CHECKLIST_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*[-*] \[[ xX]\]").expect("exprs in CHECKLIST_ITEM to be valid")
+});
+
 /// Track repositories that need to be tracked.
 #[instrument(skip_all, err)]
 pub(crate) async fn run(cfg: &Config, db: DynDB, gh: DynGH) -> Result<()> {
@@ -33,28 +88,43 @@ pub(crate) async fn run(cfg: &Config, db: DynDB, gh: DynGH) -> Result<()> {
         bail!("GitHub tokens not found in config file (creds.githubTokens)");
     }
     let gh_tokens_pool = Pool::from(gh_tokens.clone());
+    let track_options = TrackOptions {
+        pull_requests: cfg.get_bool("tracker.trackPullRequests")?,
+        discussions: cfg.get_bool("tracker.trackDiscussions")?,
+        discussions_categories: cfg.get("tracker.discussionsCategories")?,
+    };
+    let duplicates_similarity_threshold = cfg.get_float("tracker.duplicatesSimilarityThreshold")?;
 
     // Get repositories to track
+    let mut run = Run::new();
     debug!("getting repositories to track");
-    let repositories_to_track = db.get_repositories_to_track().await?;
+    let repositories_to_track = match db.get_repositories_to_track().await {
+        Ok(repositories_to_track) => repositories_to_track,
+        Err(err) => {
+            run.errors.push(format!("{err:#}"));
+            register_run(&db, &run).await;
+            return Err(err);
+        }
+    };
     if repositories_to_track.is_empty() {
+        register_run(&db, &run).await;
         info!("no repositories to track, finished");
         return Ok(());
     }
 
     // Track repositories
     info!("tracking repositories");
-    #[allow(clippy::manual_try_fold)]
-    let result = stream::iter(repositories_to_track)
+    let mut results = stream::iter(repositories_to_track)
         .map(|repository| async {
             let db = db.clone();
             let gh = gh.clone();
             let gh_token = gh_tokens_pool.get().await.expect("token -when available-");
             let repo_url = repository.url.clone();
+            let track_options = track_options.clone();
 
             match timeout(
                 Duration::from_secs(REPOSITORY_TRACK_TIMEOUT),
-                track_repository(db, gh, gh_token, repository),
+                track_repository(db, gh, gh_token, repository, track_options),
             )
             .await
             {
@@ -65,9 +135,31 @@ pub(crate) async fn run(cfg: &Config, db: DynDB, gh: DynGH) -> Result<()> {
         })
         .buffer_unordered(cfg.get("tracker.concurrency")?)
         .collect::<Vec<Result<()>>>()
+        .await;
+
+    // Mark duplicate issues (the same issue may be mirrored across several
+    // repositories of the same project)
+    debug!("marking duplicate issues");
+    if let Err(err) = db
+        .mark_duplicate_issues(duplicates_similarity_threshold)
         .await
-        .into_iter()
-        .fold(
+    {
+        results.push(Err(err.context("error marking duplicate issues")));
+    }
+
+    // Register run in database
+    for result in &results {
+        match result {
+            Ok(()) => run.processed += 1,
+            Err(err) => run.errors.push(format!("{err:#}")),
+        }
+    }
+    register_run(&db, &run).await;
+
+    // Prepare final result
+    #[allow(clippy::manual_try_fold)]
+    let result =
+        results.into_iter().fold(
             Ok::<(), Error>(()),
             |final_result, task_result| match task_result {
                 Ok(()) => final_result,
@@ -107,6 +199,7 @@ async fn track_repository(
     gh: DynGH,
     gh_token: Object<String>,
     mut repo: Repository,
+    track_options: TrackOptions,
 ) -> Result<()> {
     let start = Instant::now();
     debug!("started");
@@ -116,48 +209,119 @@ async fn track_repository(
         .repository(&gh_token, &repo.url, repo.issues_filter_label.as_ref())
         .await?;
 
-    // Update repository's GitHub data in db if needed
-    let changed = repo.update_gh_data(&gh_repo)?;
-    if changed {
-        db.update_repository_gh_data(&repo).await?;
-        debug!("github data updated in database");
+    // Update repository's GitHub data
+    let gh_data_changed = repo.update_gh_data(&gh_repo)?;
+
+    // Fetch repository pull requests and discussions from GitHub if enabled
+    // (issues from inactive repositories are not tracked, so any existing
+    // ones will be unregistered)
+    let active = repo.is_active();
+    let mut issues_in_gh = if active {
+        gh_repo.issues()
+    } else {
+        debug!(
+            archived = repo.archived,
+            disabled = repo.disabled,
+            "inactive repository"
+        );
+        vec![]
+    };
+    if active && track_options.pull_requests {
+        let gh_pull_requests = gh
+            .pull_requests(&gh_token, &repo.url, repo.issues_filter_label.as_ref())
+            .await?;
+        issues_in_gh.extend(gh_pull_requests.pull_requests());
+    }
+    if active && track_options.discussions {
+        let gh_discussions = gh.discussions(&gh_token, &repo.url).await?;
+        issues_in_gh.extend(gh_discussions.discussions(
+            repo.issues_filter_label.as_ref(),
+            &track_options.discussions_categories,
+        ));
+    }
+
+    // Apply repository specific options (i.e. label overrides)
+    for issue in &mut issues_in_gh {
+        issue.apply_repository_options(&repo);
     }
 
     // Sync issues in GitHub with database
-    let mut issues_in_gh = gh_repo.issues();
     let issues_in_db = db.get_repository_issues(repo.repository_id).await?;
 
-    // Register/update new or outdated issues
-    for issue in &mut issues_in_gh {
-        let digest_in_db = find_issue(issue.issue_id, &issues_in_db);
+    // Collect new or outdated issues that need to be registered
+    let mut issues_to_register = vec![];
+    for issue in &issues_in_gh {
+        // The digest of issues in the database may have been reset (i.e. when
+        // the project's category changes) to force registering them again
+        let digest_in_db = find_issue(issue, &issues_in_db).and_then(|i| i.digest.clone());
         if issue.digest != digest_in_db {
-            db.register_issue(&repo, issue).await?;
             debug!(issue.number, "registering issue");
+            issues_to_register.push(issue.clone());
         }
     }
 
-    // Unregister issues no longer available in GitHub
+    // Collect issues no longer available in GitHub that need to be unregistered
+    let mut issues_to_unregister = vec![];
     for issue in &issues_in_db {
-        if find_issue(issue.issue_id, &issues_in_gh).is_none() {
-            db.unregister_issue(issue.issue_id).await?;
+        if find_issue(issue, &issues_in_gh).is_none() {
             debug!(issue.number, "unregistering issue");
+            issues_to_unregister.push((issue.issue_id, issue.issue_type.clone()));
         }
     }
 
-    // Update repository's last track timestamp in db
-    db.update_repository_last_track_ts(repo.repository_id)
-        .await?;
+    // Apply all changes in the database atomically
+    db.sync_repository(&RepositorySync {
+        repository: repo,
+        gh_data_changed,
+        issues_to_register,
+        issues_to_unregister,
+    })
+    .await?;
 
     debug!(duration_ms = start.elapsed().as_millis(), "completed");
     Ok(())
 }
 
-/// Find an issue in the provided collection, returning its digest if found.
-fn find_issue(issue_id: i64, issues: &[Issue]) -> Option<String> {
+/// Register the run provided in the database. Errors are only logged, as they
+/// should not affect the result of the run.
+async fn register_run(db: &DynDB, run: &Run) {
+    if let Err(err) = db.register_run(run).await {
+        error!(?err, "error registering run");
+    }
+}
+
+/// Find an issue in the provided collection.
+fn find_issue<'a>(issue: &Issue, issues: &'a [Issue]) -> Option<&'a Issue> {
     issues
         .iter()
-        .find(|i| i.issue_id == issue_id)
-        .map(|i| i.digest.clone().expect("to be present"))
+        .find(|i| i.issue_id == issue.issue_id && i.issue_type == issue.issue_type)
+}
+
+/// Options that control what is tracked in each of the repositories.
+#[derive(Debug, Clone, Default)]
+struct TrackOptions {
+    pull_requests: bool,
+    discussions: bool,
+    discussions_categories: Vec<String>,
+}
+
+/// Tracker run information.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Run {
+    pub started_at: OffsetDateTime,
+    pub processed: i32,
+    pub errors: Vec<String>,
+}
+
+impl Run {
+    /// Create a new Run instance.
+    fn new() -> Self {
+        Self {
+            started_at: OffsetDateTime::now_utc(),
+            processed: 0,
+            errors: vec![],
+        }
+    }
 }
 
 /// Repository information.
@@ -168,13 +332,30 @@ pub(crate) struct Repository {
     pub name: String,
     pub description: Option<String>,
     pub url: String,
+    pub github_node_id: Option<String>,
     pub homepage_url: Option<String>,
+    pub contributing_url: Option<String>,
+    pub code_of_conduct_url: Option<String>,
+    pub license: Option<String>,
+    pub default_branch: Option<String>,
+    pub archived: bool,
+    pub disabled: bool,
+    pub last_commit_at: Option<OffsetDateTime>,
+    pub median_first_response_secs: Option<i64>,
+    pub open_pull_requests: Option<i32>,
     pub topics: Option<Vec<String>>,
     pub languages: Option<Vec<String>>,
+    pub language_sizes: Option<BTreeMap<String, i64>>,
+    pub language_shares: Option<BTreeMap<String, f64>>,
+    pub primary_language: Option<String>,
     pub stars: Option<i32>,
     pub digest: Option<String>,
     pub issues_filter_label: Option<String>,
+    pub label_overrides: Option<HashMap<String, String>>,
+    pub docs_only: bool,
     pub project_name: String,
+    pub project_category: Option<String>,
+    pub project_subcategory: Option<String>,
     pub foundation_id: String,
 }
 
@@ -188,16 +369,54 @@ impl Repository {
         // Homepage url
         self.homepage_url.clone_from(&gh_repo.homepage_url);
 
-        // Languages
-        self.languages = gh_repo.languages.as_ref().and_then(|languages| {
-            languages.nodes.as_ref().map(|nodes| {
-                nodes
-                    .iter()
-                    .flatten()
-                    .map(|node| node.name.clone())
-                    .collect()
-            })
-        });
+        // Contributing guidelines and code of conduct urls
+        self.contributing_url = gh_repo
+            .contributing_guidelines
+            .as_ref()
+            .and_then(|cg| cg.url.clone());
+        self.code_of_conduct_url = gh_repo
+            .code_of_conduct
+            .as_ref()
+            .and_then(|coc| coc.url.clone());
+
+        // License (SPDX id when available, name otherwise)
+        self.license =
+            gh_repo
+                .license_info
+                .as_ref()
+                .map(|license| match license.spdx_id.as_deref() {
+                    Some(spdx_id) if spdx_id != "NOASSERTION" => spdx_id.to_string(),
+                    _ => license.name.clone(),
+                });
+
+        // Default branch
+        self.default_branch = gh_repo
+            .default_branch_ref
+            .as_ref()
+            .map(|branch| branch.name.clone());
+
+        // Health signals
+        self.archived = gh_repo.is_archived;
+        self.disabled = gh_repo.is_disabled;
+        self.last_commit_at = gh_repo.last_commit_at();
+        self.median_first_response_secs = gh_repo.median_first_response_secs();
+        self.open_pull_requests = Some(gh_repo.pull_requests.total_count as i32);
+
+        // GitHub node id (used to keep track of the repository when renamed)
+        self.github_node_id = Some(gh_repo.id.clone());
+
+        // Languages (names are ordered by size, so the first one is the
+        // repository's primary language)
+        let language_sizes = gh_repo.language_sizes();
+        self.languages = language_sizes
+            .as_ref()
+            .map(|sizes| sizes.iter().map(|(name, _)| name.clone()).collect());
+        self.primary_language = self
+            .languages
+            .as_ref()
+            .and_then(|languages| languages.first().cloned());
+        self.language_sizes = language_sizes.map(|sizes| sizes.into_iter().collect());
+        self.language_shares = gh_repo.language_shares();
 
         // Stars
         self.stars = Some(gh_repo.stargazer_count as i32);
@@ -217,13 +436,31 @@ impl Repository {
         Ok(self.digest != prev_digest)
     }
 
+    /// Check if the repository is active. Issues from archived or disabled
+    /// repositories can't be worked on, so they are not tracked until the
+    /// repository becomes active again.
+    fn is_active(&self) -> bool {
+        !self.archived && !self.disabled
+    }
+
     /// Update repository's digest.
     fn update_digest(&mut self) -> Result<()> {
         let data = bincode::serde::encode_to_vec(
             (
                 &self.description,
+                &self.github_node_id,
                 &self.homepage_url,
+                &self.contributing_url,
+                &self.code_of_conduct_url,
+                &self.license,
+                &self.default_branch,
+                &self.archived,
+                &self.disabled,
+                &self.last_commit_at.map(OffsetDateTime::unix_timestamp),
+                &self.median_first_response_secs,
+                &self.open_pull_requests,
                 &self.languages,
+                &self.language_sizes,
                 &self.topics,
                 &self.stars,
             ),
@@ -235,6 +472,30 @@ impl Repository {
     }
 }
 
+/// Changes to apply to a repository in the database once it's been tracked.
+/// They are applied as a single unit of work, so either all of them or none
+/// will be applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RepositorySync {
+    pub repository: Repository,
+    pub gh_data_changed: bool,
+    pub issues_to_register: Vec<Issue>,
+    pub issues_to_unregister: Vec<(i64, IssueType)>,
+}
+
+/// Issue type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSql, FromSql)]
+#[serde(rename_all = "kebab-case")]
+#[postgres(name = "issue_type")]
+pub enum IssueType {
+    #[postgres(name = "issue")]
+    Issue,
+    #[postgres(name = "pull_request")]
+    PullRequest,
+    #[postgres(name = "discussion")]
+    Discussion,
+}
+
 /// Issue area.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSql, FromSql)]
 #[serde(rename_all = "kebab-case")]
@@ -281,20 +542,36 @@ pub(crate) struct Issue {
     pub labels: Vec<String>,
     pub published_at: OffsetDateTime,
     pub has_linked_prs: bool,
+    pub issue_type: IssueType,
     pub digest: Option<String>,
     pub area: Option<IssueArea>,
     pub kind: Option<IssueKind>,
     pub difficulty: Option<IssueDifficulty>,
+    pub estimated_difficulty: Option<IssueDifficulty>,
     pub mentor_available: Option<bool>,
     pub mentor: Option<String>,
     pub good_first_issue: Option<bool>,
+    pub skills: Vec<String>,
 }
 
 impl Issue {
     /// Update issue's digest.
     pub(crate) fn update_digest(&mut self) {
         let Ok(data) = bincode::serde::encode_to_vec(
-            (&self.title, &self.labels, &self.has_linked_prs),
+            (
+                &self.title,
+                &self.labels,
+                &self.has_linked_prs,
+                (
+                    &self.area,
+                    &self.kind,
+                    &self.difficulty,
+                    &self.estimated_difficulty,
+                    &self.mentor_available,
+                    &self.good_first_issue,
+                    &self.skills,
+                ),
+            ),
             bincode::config::legacy(),
         ) else {
             return;
@@ -310,7 +587,7 @@ impl Issue {
 
         // Weight B
         let weight_b = format!(
-            "{} {} {} {} {}",
+            "{} {} {} {} {} {} {}",
             &repo.foundation_id,
             &repo.name,
             &repo.description.clone().unwrap_or_default(),
@@ -323,7 +600,9 @@ impl Issue {
                 .languages
                 .as_ref()
                 .map(|languages| languages.join(" "))
-                .unwrap_or_default()
+                .unwrap_or_default(),
+            &repo.project_category.clone().unwrap_or_default(),
+            &repo.project_subcategory.clone().unwrap_or_default()
         )
         .trim()
         .to_owned();
@@ -338,10 +617,115 @@ impl Issue {
         }
     }
 
+    /// Apply the repository options provided to the issue. Labels are mapped
+    /// using the repository's label overrides before populating the issue
+    /// information extracted from them, and issues in docs only repositories
+    /// always belong to the docs area.
+    pub(crate) fn apply_repository_options(&mut self, repo: &Repository) {
+        if repo.label_overrides.is_none() && !repo.docs_only {
+            return;
+        }
+
+        if let Some(label_overrides) = &repo.label_overrides {
+            let labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|label| label_overrides.get(label).unwrap_or(label).clone())
+                .collect();
+            self.area = None;
+            self.kind = None;
+            self.difficulty = None;
+            self.mentor_available = None;
+            self.good_first_issue = None;
+            self.populate_from(&labels);
+        }
+        if repo.docs_only {
+            self.area = Some(IssueArea::Docs);
+        }
+        self.estimate_difficulty(None);
+        self.update_digest();
+    }
+
+    /// Estimate the issue's difficulty when it hasn't been set explicitly
+    /// using labels. Some labels (like `good first issue` or `size/XL`) are
+    /// used first. When they aren't enough, the estimation is based on the
+    /// issue's body (if provided), or the previous estimation is kept.
+    pub(crate) fn estimate_difficulty(&mut self, body: Option<&str>) {
+        if self.difficulty.is_some() {
+            self.estimated_difficulty = None;
+            return;
+        }
+        if let Some(difficulty) = self.estimate_difficulty_from_labels() {
+            self.estimated_difficulty = Some(difficulty);
+        } else if let Some(body) = body {
+            self.estimated_difficulty = estimate_difficulty_from_body(body);
+        }
+    }
+
+    /// Estimate the issue's difficulty from its labels and the information
+    /// extracted from them.
+    fn estimate_difficulty_from_labels(&self) -> Option<IssueDifficulty> {
+        for label in &self.labels {
+            match label.to_lowercase().as_str() {
+                "size/xs" | "size/s" => return Some(IssueDifficulty::Easy),
+                "size/m" => return Some(IssueDifficulty::Medium),
+                "size/l" | "size/xl" | "size/xxl" => return Some(IssueDifficulty::Hard),
+                _ => {}
+            }
+        }
+        if self.good_first_issue == Some(true) || self.area == Some(IssueArea::Docs) {
+            return Some(IssueDifficulty::Easy);
+        }
+        None
+    }
+
     /// Populate the issue with information extracted from the labels, like the
     /// issue kind, its difficulty, etc.
     pub(crate) fn populate_from_labels(&mut self) {
-        for label in &self.labels {
+        let labels = self.labels.clone();
+        self.populate_from(&labels);
+    }
+
+    /// Populate the issue's skills with the languages of the files mentioned
+    /// in the text provided (e.g. the issue's body). The issue's raw markdown
+    /// body is used instead of its plain text version, as the latter drops the
+    /// urls of links, which often point to the files involved, and the code
+    /// spans that help telling file names apart from names like `Node.js`.
+    pub(crate) fn populate_skills_from_text(&mut self, text: &str) {
+        for path in mentioned_files(text) {
+            let extension = path.rsplit('.').next().unwrap_or_default();
+            if let Some(language) = source_file_language(extension) {
+                self.add_skill(language);
+            }
+        }
+    }
+
+    /// Add the skill provided to the issue if it wasn't already present.
+    fn add_skill(&mut self, skill: &str) {
+        if !self.skills.iter().any(|s| s == skill) {
+            self.skills.push(skill.to_string());
+        }
+    }
+
+    /// Populate the issue with information extracted from the labels provided.
+    fn populate_from(&mut self, labels: &[String]) {
+        for label in labels {
+            // Skills (language labels like `lang/go` or `rust`, and labels like
+            // `area/networking`)
+            let label_lowercase = label.to_lowercase();
+            let skill = SKILLS_LABELS_PREFIXES
+                .iter()
+                .find_map(|prefix| label_lowercase.strip_prefix(prefix))
+                .filter(|skill| !skill.is_empty());
+            let language = SKILLS_LANGUAGES.iter().find(|(_, aliases, _)| {
+                aliases.contains(&skill.unwrap_or(label_lowercase.as_str()))
+            });
+            if let Some((language, _, _)) = language {
+                self.add_skill(language);
+            } else if let Some(skill) = skill {
+                self.add_skill(&skill_display_name(skill));
+            }
+
             // Area
             if label.contains("docs") || label.contains("documentation") {
                 self.area = Some(IssueArea::Docs);
@@ -397,6 +781,83 @@ impl Issue {
     }
 }
 
+/// Return the display name of the skill provided, capitalizing its words (e.g.
+/// `machine-learning` becomes `Machine Learning`).
+fn skill_display_name(skill: &str) -> String {
+    skill
+        .split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Estimate the difficulty of an issue from its body. The longer the body, the
+/// more checklist items it has or the more files it mentions, the harder the
+/// issue is likely to be. A short body is not enough evidence of an issue
+/// being easy, so no estimation is returned when the signal is weak.
+fn estimate_difficulty_from_body(body: &str) -> Option<IssueDifficulty> {
+    if body.trim().is_empty() {
+        return None;
+    }
+
+    let length_score = match body.chars().count() {
+        0..500 => 0,
+        500..2000 => 1,
+        _ => 2,
+    };
+    let checklist_score = match CHECKLIST_ITEM.find_iter(body).count() {
+        0..3 => 0,
+        3..6 => 1,
+        _ => 2,
+    };
+    let files_score = match mentioned_files(body).len() {
+        0..2 => 0,
+        2..5 => 1,
+        _ => 2,
+    };
+    match length_score + checklist_score + files_score {
+        0..2 => None,
+        2..4 => Some(IssueDifficulty::Medium),
+        _ => Some(IssueDifficulty::Hard),
+    }
+}
+
+/// Return the paths of the files mentioned in the text provided. Urls are not
+/// considered files (except for the path of the files they link to in GitHub
+/// repositories), and to avoid mistaking things like `Node.js` or `e.g.` for
+/// files, only paths including a directory or names with a source file
+/// extension found in code spans or blocks are returned.
+fn mentioned_files(text: &str) -> Vec<String> {
+    let text = URL.replace_all(text, "$path");
+    let code_ranges: Vec<_> = CODE.find_iter(&text).map(|m| m.range()).collect();
+    FILE_PATH
+        .captures_iter(&text)
+        .filter(|captures| {
+            let path = &captures[0];
+            let start = captures.get(0).map_or(0, |m| m.start());
+            let in_code = code_ranges.iter().any(|range| range.contains(&start));
+            path.contains('/')
+                || (in_code && source_file_language(&captures["extension"]).is_some())
+        })
+        .map(|captures| captures[0].to_string())
+        .collect()
+}
+
+/// Return the language of the source files with the extension provided.
+fn source_file_language(extension: &str) -> Option<&'static str> {
+    let extension = extension.to_lowercase();
+    SKILLS_LANGUAGES
+        .iter()
+        .find(|(_, _, extensions)| extensions.contains(&extension.as_str()))
+        .map(|(language, _, _)| *language)
+}
+
 /// Texts used to build the issue's text search document.
 #[derive(Debug, Clone, PartialEq)]
 #[allow(clippy::struct_field_names)]
@@ -411,7 +872,7 @@ mod tests {
     use super::*;
     use crate::{
         db::MockDB,
-        github::{MockGH, repo_view::*},
+        github::{MockGH, pull_requests_view::*, repo_view::*},
     };
     use futures::future;
     use mockall::predicate::eq;
@@ -430,17 +891,28 @@ mod tests {
         let mut repo = Repository {
             repository_id: *REPOSITORY_ID,
             url: REPOSITORY_URL.to_string(),
+            github_node_id: Some("R_1".to_string()),
             stars: Some(0),
+            open_pull_requests: Some(0),
             digest: Some(
-                "ac07f8e8751d1696c492c15d0b812feeb05c783b7584385986770192a058a85b".to_string(),
+                "cdbff9a3052362e9b73f4fce87d3d49c0767cd9239c5f87eaf62cb93c9d1b334".to_string(),
             ),
             ..Default::default()
         };
         let gh_repo = RepoViewRepository {
+            code_of_conduct: None,
+            contributing_guidelines: None,
+            default_branch_ref: None,
             description: None,
             homepage_url: Some(REPOSITORY_URL.to_string()),
+            id: "R_1".to_string(),
+            is_archived: false,
+            is_disabled: false,
             issues: RepoViewRepositoryIssues { nodes: None },
             languages: None,
+            license_info: None,
+            pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+            recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
             repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
             stargazer_count: 0,
         };
@@ -453,17 +925,27 @@ mod tests {
         let mut repo = Repository {
             repository_id: *REPOSITORY_ID,
             url: REPOSITORY_URL.to_string(),
+            github_node_id: Some("R_1".to_string()),
             stars: Some(0),
             digest: Some(
-                "ac07f8e8751d1696c492c15d0b812feeb05c783b7584385986770192a058a85b".to_string(),
+                "cdbff9a3052362e9b73f4fce87d3d49c0767cd9239c5f87eaf62cb93c9d1b334".to_string(),
             ),
             ..Default::default()
         };
         let gh_repo = RepoViewRepository {
+            code_of_conduct: None,
+            contributing_guidelines: None,
+            default_branch_ref: None,
             description: Some("description".to_string()),
             homepage_url: Some(REPOSITORY_URL.to_string()),
+            id: "R_1".to_string(),
+            is_archived: false,
+            is_disabled: false,
             issues: RepoViewRepositoryIssues { nodes: None },
             languages: None,
+            license_info: None,
+            pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+            recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
             repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
             stargazer_count: 0,
         };
@@ -471,10 +953,219 @@ mod tests {
         assert!(repo.update_gh_data(&gh_repo).unwrap());
         assert_eq!(
             repo.digest,
-            Some("5a31ae9579be1f97427de912fdd1943a9b0ef07cefe07b23a614b8d02384b7ea".to_string())
+            Some("878e284b69a77feea09adc7ab4f00c5680e4d60ecde08a0c08bf222df199d745".to_string())
         );
     }
 
+    #[test]
+    fn repository_update_gh_data_community_fields() {
+        let mut repo = Repository {
+            repository_id: *REPOSITORY_ID,
+            url: REPOSITORY_URL.to_string(),
+            ..Default::default()
+        };
+        let mut gh_repo = RepoViewRepository {
+            code_of_conduct: Some(RepoViewRepositoryCodeOfConduct {
+                url: Some("https://coc.url".to_string()),
+            }),
+            contributing_guidelines: Some(RepoViewRepositoryContributingGuidelines {
+                url: Some("https://contributing.url".to_string()),
+            }),
+            default_branch_ref: Some(RepoViewRepositoryDefaultBranchRef {
+                name: "main".to_string(),
+                target: None,
+            }),
+            description: None,
+            homepage_url: None,
+            id: "R_1".to_string(),
+            is_archived: false,
+            is_disabled: false,
+            issues: RepoViewRepositoryIssues { nodes: None },
+            languages: None,
+            license_info: Some(RepoViewRepositoryLicenseInfo {
+                name: "Apache License 2.0".to_string(),
+                spdx_id: Some("Apache-2.0".to_string()),
+            }),
+            pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+            recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
+            repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
+            stargazer_count: 0,
+        };
+
+        assert!(repo.update_gh_data(&gh_repo).unwrap());
+        assert_eq!(
+            repo.contributing_url,
+            Some("https://contributing.url".to_string())
+        );
+        assert_eq!(
+            repo.code_of_conduct_url,
+            Some("https://coc.url".to_string())
+        );
+        assert_eq!(repo.license, Some("Apache-2.0".to_string()));
+        assert_eq!(repo.default_branch, Some("main".to_string()));
+
+        // Licenses without a SPDX id are identified by their name
+        gh_repo.license_info = Some(RepoViewRepositoryLicenseInfo {
+            name: "Other".to_string(),
+            spdx_id: Some("NOASSERTION".to_string()),
+        });
+        assert!(repo.update_gh_data(&gh_repo).unwrap());
+        assert_eq!(repo.license, Some("Other".to_string()));
+    }
+
+    #[test]
+    fn repository_update_gh_data_languages() {
+        let mut repo = Repository {
+            repository_id: *REPOSITORY_ID,
+            url: REPOSITORY_URL.to_string(),
+            ..Default::default()
+        };
+        let language_edge = |name: &str, size: i64| {
+            Some(RepoViewRepositoryLanguagesEdges {
+                node: RepoViewRepositoryLanguagesEdgesNode {
+                    name: name.to_string(),
+                },
+                size,
+            })
+        };
+        let gh_repo = RepoViewRepository {
+            code_of_conduct: None,
+            contributing_guidelines: None,
+            default_branch_ref: None,
+            description: None,
+            homepage_url: None,
+            id: "R_1".to_string(),
+            is_archived: false,
+            is_disabled: false,
+            issues: RepoViewRepositoryIssues { nodes: None },
+            languages: Some(RepoViewRepositoryLanguages {
+                edges: Some(vec![language_edge("Go", 9000), language_edge("Shell", 990)]),
+                total_size: 10000,
+            }),
+            license_info: None,
+            pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+            recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
+            repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
+            stargazer_count: 0,
+        };
+
+        assert!(repo.update_gh_data(&gh_repo).unwrap());
+        assert_eq!(
+            repo.languages,
+            Some(vec!["Go".to_string(), "Shell".to_string()])
+        );
+        assert_eq!(repo.primary_language, Some("Go".to_string()));
+        assert_eq!(
+            repo.language_sizes,
+            Some(BTreeMap::from([
+                ("Go".to_string(), 9000),
+                ("Shell".to_string(), 990)
+            ]))
+        );
+        assert_eq!(
+            repo.language_shares,
+            Some(BTreeMap::from([
+                ("Go".to_string(), 90.0),
+                ("Shell".to_string(), 9.9)
+            ]))
+        );
+    }
+
+    #[test]
+    fn repository_update_gh_data_health_signals() {
+        let author = |login: &str| {
+            Some(RepoViewRepositoryRecentIssuesNodesAuthor {
+                login: login.to_string(),
+                on: RepoViewRepositoryRecentIssuesNodesAuthorOn::User,
+            })
+        };
+        let comment = |login: &str, created_at: &str| {
+            Some(RepoViewRepositoryRecentIssuesNodesCommentsNodes {
+                author: Some(RepoViewRepositoryRecentIssuesNodesCommentsNodesAuthor {
+                    login: login.to_string(),
+                    on: RepoViewRepositoryRecentIssuesNodesCommentsNodesAuthorOn::User,
+                }),
+                created_at: created_at.to_string(),
+            })
+        };
+        let bot_comment = |login: &str, created_at: &str| {
+            Some(RepoViewRepositoryRecentIssuesNodesCommentsNodes {
+                author: Some(RepoViewRepositoryRecentIssuesNodesCommentsNodesAuthor {
+                    login: login.to_string(),
+                    on: RepoViewRepositoryRecentIssuesNodesCommentsNodesAuthorOn::Bot,
+                }),
+                created_at: created_at.to_string(),
+            })
+        };
+        let issue = |created_at: &str, comments| {
+            Some(RepoViewRepositoryRecentIssuesNodes {
+                author: author("author"),
+                comments: RepoViewRepositoryRecentIssuesNodesComments {
+                    nodes: Some(comments),
+                },
+                created_at: created_at.to_string(),
+            })
+        };
+        let mut repo = Repository {
+            repository_id: *REPOSITORY_ID,
+            url: REPOSITORY_URL.to_string(),
+            ..Default::default()
+        };
+        let gh_repo = RepoViewRepository {
+            code_of_conduct: None,
+            contributing_guidelines: None,
+            default_branch_ref: Some(RepoViewRepositoryDefaultBranchRef {
+                name: "main".to_string(),
+                target: Some(RepoViewRepositoryDefaultBranchRefTarget::Commit(
+                    RepoViewRepositoryDefaultBranchRefTargetOnCommit {
+                        committed_date: "2023-01-02T03:04:05Z".to_string(),
+                    },
+                )),
+            }),
+            description: None,
+            homepage_url: None,
+            id: "R_1".to_string(),
+            is_archived: true,
+            is_disabled: false,
+            issues: RepoViewRepositoryIssues { nodes: None },
+            languages: None,
+            license_info: None,
+            pull_requests: RepoViewRepositoryPullRequests { total_count: 3 },
+            recent_issues: RepoViewRepositoryRecentIssues {
+                nodes: Some(vec![
+                    // Responded after 1 hour (author's and bots' comments
+                    // are ignored)
+                    issue(
+                        "2023-01-01T00:00:00Z",
+                        vec![
+                            bot_comment("github-actions", "2023-01-01T00:01:00Z"),
+                            comment("author", "2023-01-01T00:30:00Z"),
+                            comment("maintainer", "2023-01-01T01:00:00Z"),
+                        ],
+                    ),
+                    // Responded after 3 hours
+                    issue(
+                        "2023-01-01T00:00:00Z",
+                        vec![comment("maintainer", "2023-01-01T03:00:00Z")],
+                    ),
+                    // Not responded yet
+                    issue("2023-01-01T00:00:00Z", vec![]),
+                ]),
+            },
+            repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
+            stargazer_count: 0,
+        };
+
+        assert!(repo.update_gh_data(&gh_repo).unwrap());
+        assert!(repo.archived);
+        assert_eq!(
+            repo.last_commit_at,
+            Some(OffsetDateTime::parse("2023-01-02T03:04:05Z", &Rfc3339).unwrap())
+        );
+        assert_eq!(repo.median_first_response_secs, Some(7200));
+        assert_eq!(repo.open_pull_requests, Some(3));
+    }
+
     #[test]
     fn repository_update_digest() {
         let mut repo = Repository {
@@ -487,7 +1178,7 @@ mod tests {
         repo.update_digest().unwrap();
         assert_eq!(
             repo.digest,
-            Some("cdb032de4c6cb506da0606e0934e69ad1ae64773ffaa76f9d6e28192067c43cf".to_string())
+            Some("31736a8b5d336a7a1d672baf93ee3461e5548e816b73c659c1445e401ba85b15".to_string())
         );
     }
 
@@ -501,22 +1192,65 @@ mod tests {
             labels: vec!["label1".to_string()],
             published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
             has_linked_prs: false,
+            issue_type: IssueType::Issue,
             digest: None,
             area: None,
             kind: None,
             difficulty: None,
+            estimated_difficulty: None,
             mentor_available: None,
             mentor: None,
             good_first_issue: None,
+            skills: vec![],
         };
 
         issue.update_digest();
         assert_eq!(
             issue.digest,
-            Some("bfd1f875bce09b3edc4adc1553431e887ae70f429d549cbd746adc722243aafd".to_string())
+            Some("236e7e78f01173765749b80714aff421b5eed16daf9cc93ec0348367e6cf3e5a".to_string())
         );
     }
 
+    #[test]
+    fn issue_apply_repository_options() {
+        let repo = Repository {
+            label_overrides: Some(HashMap::from([
+                ("beginner".to_string(), "good first issue".to_string()),
+                ("defect".to_string(), "bug".to_string()),
+            ])),
+            docs_only: true,
+            ..Default::default()
+        };
+        let mut issue = Issue {
+            issue_id: 1,
+            title: "issue1".to_string(),
+            url: "issue1_url".to_string(),
+            number: 1,
+            labels: vec!["beginner".to_string(), "defect".to_string()],
+            published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
+            has_linked_prs: false,
+            issue_type: IssueType::Issue,
+            digest: None,
+            area: None,
+            kind: None,
+            difficulty: None,
+            estimated_difficulty: None,
+            mentor_available: None,
+            mentor: None,
+            good_first_issue: None,
+            skills: vec![],
+        };
+        issue.populate_from_labels();
+        assert_eq!(issue.kind, None);
+
+        issue.apply_repository_options(&repo);
+        assert_eq!(issue.labels, vec!["beginner", "defect"]);
+        assert_eq!(issue.area, Some(IssueArea::Docs));
+        assert_eq!(issue.kind, Some(IssueKind::Bug));
+        assert_eq!(issue.good_first_issue, Some(true));
+        assert!(issue.digest.is_some());
+    }
+
     #[test]
     fn issue_prepare_ts_texts() {
         let repo = Repository {
@@ -527,6 +1261,8 @@ mod tests {
             topics: Some(vec!["topic1".to_string(), "topic2".to_string()]),
             languages: Some(vec!["language1".to_string()]),
             project_name: "project".to_string(),
+            project_category: Some("category".to_string()),
+            project_subcategory: Some("subcategory".to_string()),
             foundation_id: "foundation".to_string(),
             ..Default::default()
         };
@@ -538,20 +1274,25 @@ mod tests {
             labels: vec!["label1".to_string(), "label2".to_string()],
             published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
             has_linked_prs: false,
+            issue_type: IssueType::Issue,
             digest: None,
             area: None,
             kind: None,
             difficulty: None,
+            estimated_difficulty: None,
             mentor_available: None,
             mentor: None,
             good_first_issue: None,
+            skills: vec![],
         };
 
         assert_eq!(
             issue.prepare_ts_texts(&repo),
             IssueTsTexts {
                 weight_a: "project".to_string(),
-                weight_b: "foundation repo description topic1 topic2 language1".to_string(),
+                weight_b:
+                    "foundation repo description topic1 topic2 language1 category subcategory"
+                        .to_string(),
                 weight_c: "issue1 label1 label2".to_string(),
             },
         );
@@ -573,13 +1314,16 @@ mod tests {
             ],
             published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
             has_linked_prs: false,
+            issue_type: IssueType::Issue,
             digest: None,
             area: None,
             kind: None,
             difficulty: None,
+            estimated_difficulty: None,
             mentor_available: None,
             mentor: None,
             good_first_issue: None,
+            skills: vec![],
         };
 
         issue.populate_from_labels();
@@ -590,6 +1334,196 @@ mod tests {
         assert_eq!(issue.good_first_issue, Some(true));
     }
 
+    #[test]
+    fn issue_estimate_difficulty() {
+        let issue = Issue {
+            issue_id: 1,
+            title: "issue1".to_string(),
+            url: "issue1_url".to_string(),
+            number: 1,
+            labels: vec![],
+            published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
+            has_linked_prs: false,
+            issue_type: IssueType::Issue,
+            digest: None,
+            area: None,
+            kind: None,
+            difficulty: None,
+            estimated_difficulty: None,
+            mentor_available: None,
+            mentor: None,
+            good_first_issue: None,
+            skills: vec![],
+        };
+        let estimate = |labels: &[&str], body: Option<&str>| {
+            let mut issue = Issue {
+                labels: labels.iter().map(ToString::to_string).collect(),
+                ..issue.clone()
+            };
+            issue.populate_from_labels();
+            issue.estimate_difficulty(body);
+            issue.estimated_difficulty
+        };
+        let checklist = "- [ ] step\n".repeat(6);
+        let files = "pkg/a.go pkg/b.go pkg/c.go pkg/d.go pkg/e.go pkg/f.go";
+
+        // Explicit difficulty labels take precedence
+        assert_eq!(estimate(&["difficulty/hard"], Some("")), None);
+
+        // Labels
+        assert_eq!(estimate(&["size/XL"], None), Some(IssueDifficulty::Hard));
+        assert_eq!(estimate(&["size/M"], None), Some(IssueDifficulty::Medium));
+        assert_eq!(
+            estimate(&["good first issue"], Some(&checklist)),
+            Some(IssueDifficulty::Easy)
+        );
+        assert_eq!(
+            estimate(&["documentation"], None),
+            Some(IssueDifficulty::Easy)
+        );
+
+        // Body
+        assert_eq!(estimate(&[], None), None);
+        assert_eq!(estimate(&[], Some("")), None);
+        assert_eq!(estimate(&[], Some("Fix typo")), None);
+        assert_eq!(estimate(&[], Some(&"x".repeat(600))), None);
+        assert_eq!(
+            estimate(&[], Some(&format!("{checklist}{files}"))),
+            Some(IssueDifficulty::Hard)
+        );
+        assert_eq!(
+            estimate(&[], Some(&format!("{}{checklist}", "x".repeat(600)))),
+            Some(IssueDifficulty::Medium)
+        );
+    }
+
+    #[test]
+    fn mentioned_files_ignores_links_and_abbreviations() {
+        let body = "See https://github.com/org/repo/issues/1 and \
+            [the docs](https://kubernetes.io/docs/concepts/overview.html) (i.e. the \
+            overview), www.example.com, docs.rs, github.com and kubernetes.io. It \
+            happens with Node.js too, e.g. when using Vue.js.";
+        assert!(mentioned_files(body).is_empty());
+        assert_eq!(
+            estimate_difficulty_from_body(&format!("{body}\n{}", "- [ ] step\n".repeat(3))),
+            None
+        );
+
+        let body = "The bug is in `handler.go` (see \
+            https://github.com/org/repo/blob/main/pkg/server/server.go#L10) and \
+            web/src/App.tsx.";
+        assert_eq!(
+            mentioned_files(body),
+            vec!["handler.go", "pkg/server/server.go", "web/src/App.tsx"]
+        );
+    }
+
+    #[test]
+    fn issue_populate_skills() {
+        let mut issue = Issue {
+            issue_id: 1,
+            title: "issue1".to_string(),
+            url: "issue1_url".to_string(),
+            number: 1,
+            labels: vec![
+                "lang/golang".to_string(),
+                "area/Networking".to_string(),
+                "skill/machine-learning".to_string(),
+                "Rust".to_string(),
+                "help wanted".to_string(),
+            ],
+            published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339).unwrap(),
+            has_linked_prs: false,
+            issue_type: IssueType::Issue,
+            digest: None,
+            area: None,
+            kind: None,
+            difficulty: None,
+            estimated_difficulty: None,
+            mentor_available: None,
+            mentor: None,
+            good_first_issue: None,
+            skills: vec![],
+        };
+
+        issue.populate_from_labels();
+        issue.populate_skills_from_text(
+            "The bug is in `pkg/server/handler.go`, see also web/src/App.tsx and \
+            scripts/build.sh (more details at example.com).",
+        );
+        issue.populate_skills_from_text(
+            "It fails when using Node.js (see https://docs.rs/crate/latest and docs.rs), \
+            e.g. with Vue.js.",
+        );
+        assert_eq!(
+            issue.skills,
+            vec![
+                "Go".to_string(),
+                "Networking".to_string(),
+                "Machine Learning".to_string(),
+                "Rust".to_string(),
+                "TypeScript".to_string(),
+                "Shell".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn discussions_filtered_by_label_and_category() {
+        use crate::github::discussions_view::*;
+
+        let discussion = |id: i64, category: &str, label: &str| {
+            Some(DiscussionsViewRepositoryDiscussionsNodes {
+                database_id: Some(id),
+                title: format!("discussion{id}"),
+                url: format!("discussion{id}_url"),
+                number: id,
+                published_at: Some("1985-04-12T23:20:50.52Z".to_string()),
+                category: DiscussionsViewRepositoryDiscussionsNodesCategory {
+                    name: category.to_string(),
+                },
+                labels: Some(DiscussionsViewRepositoryDiscussionsNodesLabels {
+                    nodes: Some(vec![Some(
+                        DiscussionsViewRepositoryDiscussionsNodesLabelsNodes {
+                            name: label.to_string(),
+                        },
+                    )]),
+                }),
+            })
+        };
+        let gh_discussions = DiscussionsViewRepository {
+            discussions: DiscussionsViewRepositoryDiscussions {
+                nodes: Some(vec![
+                    discussion(1, "Ideas", "help wanted"),
+                    discussion(2, "Q&A", "help wanted"),
+                    discussion(3, "Ideas", "question"),
+                ]),
+                page_info: DiscussionsViewRepositoryDiscussionsPageInfo {
+                    has_next_page: false,
+                    end_cursor: None,
+                },
+            },
+        };
+
+        let discussions = gh_discussions.discussions(None, &["Ideas".to_string()]);
+        assert_eq!(discussions.len(), 1);
+        assert_eq!(discussions[0].issue_id, 1);
+        assert_eq!(discussions[0].issue_type, IssueType::Discussion);
+        assert_eq!(discussions[0].url, "discussion1_url");
+
+        let discussions = gh_discussions.discussions(None, &[]);
+        assert_eq!(
+            discussions.iter().map(|d| d.issue_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let discussions = gh_discussions.discussions(Some(&"question".to_string()), &[]);
+        assert_eq!(
+            discussions.iter().map(|d| d.issue_id).collect::<Vec<_>>(),
+            vec![3]
+        );
+    }
+
     #[tokio::test]
     async fn run_error_getting_github_tokens() {
         let cfg = Config::builder().build().unwrap();
@@ -626,6 +1560,10 @@ mod tests {
         db.expect_get_repositories_to_track()
             .times(1)
             .returning(|| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+        db.expect_register_run()
+            .withf(|run| run.processed == 0 && run.errors == vec![FAKE_ERROR.to_string()])
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
 
         let result = run(&cfg, Arc::new(db), Arc::new(gh)).await;
         assert_eq!(result.unwrap_err().to_string(), FAKE_ERROR);
@@ -640,6 +1578,10 @@ mod tests {
         db.expect_get_repositories_to_track()
             .times(1)
             .returning(|| Box::pin(future::ready(Ok(vec![]))));
+        db.expect_register_run()
+            .withf(|run| run.processed == 0 && run.errors.is_empty())
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
 
         run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
     }
@@ -664,11 +1606,62 @@ mod tests {
             })
             .times(1)
             .returning(|_, _, _| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+        db.expect_mark_duplicate_issues()
+            .with(eq(0.8))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|run| {
+                run.processed == 0
+                    && run.errors
+                        == vec![format!(
+                            "error tracking repository {REPOSITORY_URL}: {FAKE_ERROR}"
+                        )]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
 
         let result = run(&cfg, Arc::new(db), Arc::new(gh)).await;
         assert_eq!(result.unwrap_err().root_cause().to_string(), FAKE_ERROR);
     }
 
+    #[tokio::test]
+    async fn run_error_marking_duplicate_issues() {
+        let cfg = setup_test_config(&[TOKEN1]);
+        let mut db = MockDB::new();
+        let mut gh = MockGH::new();
+
+        db.expect_get_repositories_to_track()
+            .times(1)
+            .returning(|| {
+                Box::pin(future::ready(Ok(vec![Repository {
+                    url: REPOSITORY_URL.to_string(),
+                    ..Default::default()
+                }])))
+            });
+        gh.expect_repository()
+            .times(1)
+            .returning(|_, _, _| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+        db.expect_mark_duplicate_issues()
+            .with(eq(0.8))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Err(format_err!(FAKE_ERROR)))));
+        db.expect_register_run()
+            .withf(|run| {
+                run.processed == 0
+                    && run.errors
+                        == vec![
+                            format!("error tracking repository {REPOSITORY_URL}: {FAKE_ERROR}"),
+                            format!("error marking duplicate issues: {FAKE_ERROR}"),
+                        ]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+
+        let result = run(&cfg, Arc::new(db), Arc::new(gh)).await;
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     #[allow(clippy::too_many_lines)]
     async fn run_register_one_issue_and_unregister_another_successfully() {
@@ -692,10 +1685,17 @@ mod tests {
             .times(1)
             .returning(|_, _, _| {
                 Box::pin(future::ready(Ok(RepoViewRepository {
+                    code_of_conduct: None,
+                    contributing_guidelines: None,
+                    default_branch_ref: None,
                     description: Some("description".to_string()),
                     homepage_url: None,
+                    id: "R_1".to_string(),
+                    is_archived: false,
+                    is_disabled: false,
                     issues: RepoViewRepositoryIssues {
                         nodes: Some(vec![Some(RepoViewRepositoryIssuesNodes {
+                            body: "Error in src/main.rs".to_string(),
                             closed_by_pull_requests_references: Some(
                                 RepoViewRepositoryIssuesNodesClosedByPullRequestsReferences {
                                     nodes: Some(vec![
@@ -728,23 +1728,13 @@ mod tests {
                         })]),
                     },
                     languages: None,
+                    license_info: None,
+                    pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+                    recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
                     repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
                     stargazer_count: 0,
                 })))
             });
-        db.expect_update_repository_gh_data()
-            .with(eq(Repository {
-                repository_id: *REPOSITORY_ID,
-                url: REPOSITORY_URL.to_string(),
-                description: Some("description".to_string()),
-                stars: Some(0),
-                digest: Some(
-                    "16139cdd47898d43806d0fd1fb6b2596dbf618362f6b9c22a5a2ec1ec0b882f9".to_string(),
-                ),
-                ..Default::default()
-            }))
-            .times(1)
-            .returning(|_| Box::pin(future::ready(Ok(()))));
         db.expect_get_repository_issues()
             .with(eq(*REPOSITORY_ID))
             .times(1)
@@ -757,29 +1747,35 @@ mod tests {
                     labels: vec![],
                     published_at: OffsetDateTime::now_utc(),
                     has_linked_prs: true,
+                    issue_type: IssueType::Issue,
                     digest: None,
                     area: None,
                     kind: None,
                     difficulty: None,
+                    estimated_difficulty: None,
                     mentor_available: None,
                     mentor: None,
                     good_first_issue: None,
+                    skills: vec![],
                 }])))
             });
-        db.expect_register_issue()
-            .with(
-                eq(Repository {
+        db.expect_sync_repository()
+            .with(eq(RepositorySync {
+                repository: Repository {
                     repository_id: *REPOSITORY_ID,
                     url: REPOSITORY_URL.to_string(),
                     description: Some("description".to_string()),
+                    github_node_id: Some("R_1".to_string()),
                     stars: Some(0),
+                    open_pull_requests: Some(0),
                     digest: Some(
-                        "16139cdd47898d43806d0fd1fb6b2596dbf618362f6b9c22a5a2ec1ec0b882f9"
+                        "7a087a5c7301e2e5fee7d5746a379f49f8e3c0828c0de1d9ab89d833c6c6188f"
                             .to_string(),
                     ),
                     ..Default::default()
-                }),
-                eq(Issue {
+                },
+                gh_data_changed: true,
+                issues_to_register: vec![Issue {
                     issue_id: 1,
                     title: "issue1".to_string(),
                     url: "issue1_url".to_string(),
@@ -792,27 +1788,242 @@ mod tests {
                     published_at: OffsetDateTime::parse("1985-04-12T23:20:50.52Z", &Rfc3339)
                         .unwrap(),
                     has_linked_prs: true,
+                    issue_type: IssueType::Issue,
                     digest: Some(
-                        "b10bea4dd2f2cdc776db781bbfe376462eb395c859d916583555e61179f49007"
+                        "301d56a819a21f8254bd9b0d020fb76e894e7eca2cc4116886c410d94db89543"
                             .to_string(),
                     ),
                     area: None,
                     kind: Some(IssueKind::Bug),
                     difficulty: Some(IssueDifficulty::Easy),
+                    estimated_difficulty: None,
                     mentor_available: None,
                     mentor: None,
                     good_first_issue: Some(true),
-                }),
-            )
+                    skills: vec!["Rust".to_string()],
+                }],
+                issues_to_unregister: vec![(2, IssueType::Issue)],
+            }))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_mark_duplicate_issues()
+            .with(eq(0.8))
             .times(1)
-            .returning(|_, _| Box::pin(future::ready(Ok(()))));
-        db.expect_unregister_issue()
-            .with(eq(2))
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|run| run.processed == 1 && run.errors.is_empty())
             .times(1)
             .returning(|_| Box::pin(future::ready(Ok(()))));
-        db.expect_update_repository_last_track_ts()
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::too_many_lines)]
+    async fn run_archived_repository_issues_unregistered() {
+        let cfg = Config::builder()
+            .add_source(setup_test_config(&[TOKEN1]))
+            .set_override("tracker.trackPullRequests", true)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut db = MockDB::new();
+        let mut gh = MockGH::new();
+        gh.expect_repository().times(1).returning(|_, _, _| {
+            Box::pin(future::ready(Ok(RepoViewRepository {
+                code_of_conduct: None,
+                contributing_guidelines: None,
+                default_branch_ref: None,
+                description: None,
+                homepage_url: None,
+                id: "R_1".to_string(),
+                is_archived: true,
+                is_disabled: false,
+                issues: RepoViewRepositoryIssues {
+                    nodes: Some(vec![Some(RepoViewRepositoryIssuesNodes {
+                        body: String::new(),
+                        closed_by_pull_requests_references: None,
+                        database_id: Some(1),
+                        title: "issue1".to_string(),
+                        url: "issue1_url".to_string(),
+                        number: 1,
+                        published_at: Some("1985-04-12T23:20:50.52Z".to_string()),
+                        labels: None,
+                    })]),
+                },
+                languages: None,
+                license_info: None,
+                pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+                recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
+                repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
+                stargazer_count: 0,
+            })))
+        });
+
+        db.expect_get_repositories_to_track()
+            .times(1)
+            .returning(|| {
+                Box::pin(future::ready(Ok(vec![Repository {
+                    repository_id: *REPOSITORY_ID,
+                    url: REPOSITORY_URL.to_string(),
+                    ..Default::default()
+                }])))
+            });
+        db.expect_get_repository_issues()
             .with(eq(*REPOSITORY_ID))
             .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![Issue {
+                    issue_id: 1,
+                    title: "issue1".to_string(),
+                    url: "issue1_url".to_string(),
+                    number: 1,
+                    labels: vec![],
+                    published_at: OffsetDateTime::now_utc(),
+                    has_linked_prs: false,
+                    issue_type: IssueType::Issue,
+                    digest: None,
+                    area: None,
+                    kind: None,
+                    difficulty: None,
+                    estimated_difficulty: None,
+                    mentor_available: None,
+                    mentor: None,
+                    good_first_issue: None,
+                    skills: vec![],
+                }])))
+            });
+        db.expect_sync_repository()
+            .withf(|sync| {
+                sync.repository.archived
+                    && sync.gh_data_changed
+                    && sync.issues_to_register.is_empty()
+                    && sync.issues_to_unregister == vec![(1, IssueType::Issue)]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_mark_duplicate_issues()
+            .with(eq(0.8))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|run| run.processed == 1 && run.errors.is_empty())
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+
+        run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::too_many_lines)]
+    async fn run_register_pull_request_when_enabled() {
+        let cfg = Config::builder()
+            .add_source(setup_test_config(&[TOKEN1]))
+            .set_override("tracker.trackPullRequests", true)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut db = MockDB::new();
+        let mut gh = MockGH::new();
+
+        db.expect_get_repositories_to_track()
+            .times(1)
+            .returning(|| {
+                Box::pin(future::ready(Ok(vec![Repository {
+                    repository_id: *REPOSITORY_ID,
+                    url: REPOSITORY_URL.to_string(),
+                    ..Default::default()
+                }])))
+            });
+        gh.expect_repository().times(1).returning(|_, _, _| {
+            Box::pin(future::ready(Ok(RepoViewRepository {
+                code_of_conduct: None,
+                contributing_guidelines: None,
+                default_branch_ref: None,
+                description: None,
+                homepage_url: None,
+                id: "R_1".to_string(),
+                is_archived: false,
+                is_disabled: false,
+                issues: RepoViewRepositoryIssues { nodes: None },
+                languages: None,
+                license_info: None,
+                pull_requests: RepoViewRepositoryPullRequests { total_count: 0 },
+                recent_issues: RepoViewRepositoryRecentIssues { nodes: None },
+                repository_topics: RepoViewRepositoryRepositoryTopics { nodes: None },
+                stargazer_count: 0,
+            })))
+        });
+        gh.expect_pull_requests()
+            .withf(|token, repository_url, pull_requests_filter_label| {
+                token == TOKEN1
+                    && repository_url == REPOSITORY_URL
+                    && pull_requests_filter_label.is_none()
+            })
+            .times(1)
+            .returning(|_, _, _| {
+                Box::pin(future::ready(Ok(PullRequestsViewRepository {
+                    pull_requests: PullRequestsViewRepositoryPullRequests {
+                        nodes: Some(vec![Some(PullRequestsViewRepositoryPullRequestsNodes {
+                            full_database_id: Some("1".to_string()),
+                            title: "pr1".to_string(),
+                            url: "pr1_url".to_string(),
+                            number: 1,
+                            published_at: Some("1985-04-12T23:20:50.52Z".to_string()),
+                            labels: Some(PullRequestsViewRepositoryPullRequestsNodesLabels {
+                                nodes: Some(vec![Some(
+                                    PullRequestsViewRepositoryPullRequestsNodesLabelsNodes {
+                                        name: "help wanted".to_string(),
+                                    },
+                                )]),
+                            }),
+                        })]),
+                    },
+                })))
+            });
+        db.expect_get_repository_issues()
+            .with(eq(*REPOSITORY_ID))
+            .times(1)
+            .returning(|_| {
+                Box::pin(future::ready(Ok(vec![Issue {
+                    issue_id: 1,
+                    title: "issue1".to_string(),
+                    url: "issue1_url".to_string(),
+                    number: 1,
+                    labels: vec![],
+                    published_at: OffsetDateTime::now_utc(),
+                    has_linked_prs: false,
+                    issue_type: IssueType::Issue,
+                    digest: Some("digest".to_string()),
+                    area: None,
+                    kind: None,
+                    difficulty: None,
+                    estimated_difficulty: None,
+                    mentor_available: None,
+                    mentor: None,
+                    good_first_issue: None,
+                    skills: vec![],
+                }])))
+            });
+        db.expect_sync_repository()
+            .withf(|sync| {
+                sync.issues_to_register.len() == 1
+                    && sync.issues_to_register[0].issue_id == 1
+                    && sync.issues_to_register[0].issue_type == IssueType::PullRequest
+                    && sync.issues_to_register[0].url == "pr1_url"
+                    && sync.issues_to_register[0].labels == vec!["help wanted".to_string()]
+                    && !sync.issues_to_register[0].has_linked_prs
+                    && sync.issues_to_unregister == vec![(1, IssueType::Issue)]
+            })
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_mark_duplicate_issues()
+            .with(eq(0.8))
+            .times(1)
+            .returning(|_| Box::pin(future::ready(Ok(()))));
+        db.expect_register_run()
+            .withf(|run| run.processed == 1 && run.errors.is_empty())
+            .times(1)
             .returning(|_| Box::pin(future::ready(Ok(()))));
 
         run(&cfg, Arc::new(db), Arc::new(gh)).await.unwrap();
@@ -822,6 +2033,14 @@ mod tests {
         Config::builder()
             .set_default("tracker.concurrency", 1)
             .unwrap()
+            .set_default("tracker.trackPullRequests", false)
+            .unwrap()
+            .set_default("tracker.trackDiscussions", false)
+            .unwrap()
+            .set_default("tracker.discussionsCategories", Vec::<String>::new())
+            .unwrap()
+            .set_default("tracker.duplicatesSimilarityThreshold", 0.8)
+            .unwrap()
             .set_default(
                 "creds.githubTokens",
                 tokens
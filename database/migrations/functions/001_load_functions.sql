@@ -1,7 +1,13 @@
+{{ template "foundations/reconcile_foundations.sql" }}
 {{ template "issues/get_issues_filters.sql" }}
+{{ template "issues/mark_duplicate_issues.sql" }}
 {{ template "issues/search_issues.sql" }}
+{{ template "projects/get_projects_maintainers_wanted.sql" }}
+{{ template "projects/purge_unregistered_projects.sql" }}
 {{ template "projects/register_project.sql" }}
 {{ template "projects/unregister_project.sql" }}
+{{ template "runs/get_status.sql" }}
+{{ template "runs/register_run.sql" }}
 
 ---- create above / drop below ----
 
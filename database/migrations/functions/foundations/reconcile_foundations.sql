@@ -0,0 +1,60 @@
+-- reconcile_foundations registers the provided foundations in the database,
+-- updating the existing ones and disabling the ones no longer provided. The
+-- data file cache information is reset when the data file location, its format
+-- or the opt-in mode change.
+create or replace function reconcile_foundations(p_foundations jsonb)
+returns void as $$
+    insert into foundation (
+        foundation_id,
+        display_name,
+        data_url,
+        data_format,
+        enabled,
+        opt_in
+    )
+    select
+        f->>'foundation_id',
+        f->>'display_name',
+        f->>'data_url',
+        coalesce((f->>'data_format')::data_format, 'clomonitor'),
+        coalesce((f->>'enabled')::boolean, true),
+        coalesce((f->>'opt_in')::boolean, false)
+    from jsonb_array_elements(p_foundations) f
+    on conflict (foundation_id) do update
+    set
+        display_name = excluded.display_name,
+        data_url = excluded.data_url,
+        data_format = excluded.data_format,
+        enabled = excluded.enabled,
+        opt_in = excluded.opt_in,
+        data_etag = case
+            when foundation.data_url = excluded.data_url
+            and foundation.data_format = excluded.data_format
+            and foundation.opt_in = excluded.opt_in
+            then foundation.data_etag
+        end,
+        data_last_modified = case
+            when foundation.data_url = excluded.data_url
+            and foundation.data_format = excluded.data_format
+            and foundation.opt_in = excluded.opt_in
+            then foundation.data_last_modified
+        end,
+        data_digest = case
+            when foundation.data_url = excluded.data_url
+            and foundation.data_format = excluded.data_format
+            and foundation.opt_in = excluded.opt_in
+            then foundation.data_digest
+        end,
+        data_cache_version = case
+            when foundation.data_url = excluded.data_url
+            and foundation.data_format = excluded.data_format
+            and foundation.opt_in = excluded.opt_in
+            then foundation.data_cache_version
+        end;
+
+    update foundation set enabled = false
+    where foundation_id not in (
+        select f->>'foundation_id'
+        from jsonb_array_elements(p_foundations) f
+    );
+$$ language sql;
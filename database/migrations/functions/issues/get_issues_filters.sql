@@ -14,6 +14,7 @@ returns json as $$
                     from (
                         select foundation_id, display_name
                         from foundation
+                        where enabled = true
                         order by foundation_id asc
                     ) f
                 )
@@ -34,6 +35,42 @@ returns json as $$
                     ) m
                 )
             ),
+            -- Categories and subcategories are displayed as stored, as some
+            -- names cannot be capitalized automatically (e.g. CI/CD)
+            json_build_object(
+                'title', 'Category',
+                'key', 'category',
+                'options', (
+                    select coalesce(json_agg(json_build_object(
+                        'name', category,
+                        'value', category
+                    )), '[]')
+                    from (
+                        select distinct category
+                        from project
+                        where category is not null
+                        and deleted_at is null
+                        order by category asc
+                    ) c
+                )
+            ),
+            json_build_object(
+                'title', 'Subcategory',
+                'key', 'subcategory',
+                'options', (
+                    select coalesce(json_agg(json_build_object(
+                        'name', subcategory,
+                        'value', subcategory
+                    )), '[]')
+                    from (
+                        select distinct subcategory
+                        from project
+                        where subcategory is not null
+                        and deleted_at is null
+                        order by subcategory asc
+                    ) s
+                )
+            ),
             json_build_object(
                 'title', 'Project',
                 'key', 'project',
@@ -90,6 +127,19 @@ returns json as $$
                     ) d
                 )
             ),
+            json_build_object(
+                'title', 'Type',
+                'key', 'type',
+                'options', (
+                    select coalesce(json_agg(json_build_object(
+                        'name', initcap(replace(type::text, '_', ' ')),
+                        'value', type::text
+                    )), '[]')
+                    from (
+                        select unnest(enum_range(null::issue_type)) as type
+                    ) t
+                )
+            ),
             json_build_object(
                 'title', 'Language',
                 'key', 'language',
@@ -122,6 +172,11 @@ returns json as $$
                         "name": "No linked PRs",
                         "key": "no_linked_prs",
                         "type": "boolean"
+                    },
+                    {
+                        "name": "Hide inactive repositories",
+                        "key": "hide_inactive",
+                        "type": "boolean"
                     }
                 ]
             }'::jsonb
@@ -133,15 +188,29 @@ returns json as $$
                     select distinct foundation_id, array_agg(distinct maturity) as maturities
                     from project
                     where maturity is not null
+                    and deleted_at is null
                     group by foundation_id
                     order by foundation_id asc
                 ) as maturities
             ),
+            'subcategory', (
+                select coalesce(json_object_agg(category, subcategories), '{}')
+                from (
+                    select category, array_agg(distinct subcategory) as subcategories
+                    from project
+                    where category is not null
+                    and subcategory is not null
+                    and deleted_at is null
+                    group by category
+                    order by category asc
+                ) as subcategories
+            ),
             'project', (
                 select coalesce(json_object_agg(foundation_id, projects), '{}')
                 from (
                     select distinct foundation_id, array_agg(distinct name) as projects
                     from project
+                    where deleted_at is null
                     group by foundation_id
                     order by foundation_id asc
                 ) as projects
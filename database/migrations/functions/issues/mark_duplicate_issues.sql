@@ -0,0 +1,80 @@
+-- Mark the issues whose normalized title is similar enough (trigram based) to
+-- the one of an older issue of the same type in the same project as
+-- duplicates, using the oldest of those issues as the canonical one (chains of
+-- similar issues are resolved to the first issue of the chain, so that the
+-- canonical issue is never a duplicate itself). Only the
+-- projects with repositories whose issues changed since the last time are
+-- processed, and issues with short titles (that tend to be similar even when
+-- they are unrelated, like "Add unit tests") are never considered duplicates.
+create or replace function mark_duplicate_issues(p_similarity_threshold double precision)
+returns void as $$
+declare
+    v_title_min_length int := 20;
+begin
+    -- The similarity operator (%) uses this threshold, and unlike the
+    -- similarity function it can take advantage of the trigram index (it's
+    -- set locally, so that it's reset once the current transaction finishes)
+    perform set_config('pg_trgm.similarity_threshold', p_similarity_threshold::text, true);
+
+    with recursive changed_project as (
+        select distinct project_id
+        from repository
+        where issues_changed = true
+    ),
+    candidate_issue as (
+        select
+            i.issue_id,
+            i.type,
+            i.published_at,
+            i.normalized_title,
+            r.project_id
+        from issue i
+        join repository r using (repository_id)
+        where r.project_id in (select project_id from changed_project)
+        and length(i.normalized_title) >= v_title_min_length
+    ),
+    duplicate as (
+        select distinct on (d.issue_id, d.type)
+            d.issue_id,
+            d.type,
+            c.issue_id as canonical_issue_id
+        from candidate_issue d
+        join issue c on
+            c.normalized_title % d.normalized_title
+            and c.type = d.type
+            and (c.published_at, c.issue_id) < (d.published_at, d.issue_id)
+            and length(c.normalized_title) >= v_title_min_length
+        join repository cr on
+            cr.repository_id = c.repository_id
+            and cr.project_id = d.project_id
+        order by d.issue_id, d.type, c.published_at asc, c.issue_id asc
+    ),
+    resolved_duplicate as (
+        select d.issue_id, d.type, d.canonical_issue_id
+        from duplicate d
+        where not exists (
+            select 1
+            from duplicate p
+            where p.issue_id = d.canonical_issue_id
+            and p.type = d.type
+        )
+        union all
+        select d.issue_id, d.type, r.canonical_issue_id
+        from duplicate d
+        join resolved_duplicate r on
+            r.issue_id = d.canonical_issue_id
+            and r.type = d.type
+    )
+    update issue i set canonical_issue_id = d.canonical_issue_id
+    from issue n
+    join repository r using (repository_id)
+    left join resolved_duplicate d using (issue_id, type)
+    where r.project_id in (select project_id from changed_project)
+    and i.issue_id = n.issue_id
+    and i.type = n.type
+    and i.canonical_issue_id is distinct from d.canonical_issue_id;
+
+    update repository set issues_changed = false
+    where issues_changed = true;
+end
+$$ language plpgsql;
@@ -8,11 +8,18 @@ declare
     v_sort_by text := coalesce(p_input->>'sort_by', 'most_recent');
     v_foundation text[];
     v_maturity text[];
+    v_category text[];
+    v_subcategory text[];
     v_project text[];
     v_area text[];
     v_kind text[];
     v_difficulty text[];
+    v_estimated_difficulty text[];
     v_language text[];
+    v_language_min_share real := (p_input->>'language_min_share')::real;
+    v_primary_language text[];
+    v_skill text[];
+    v_type text[];
     v_tsquery_web tsquery := websearch_to_tsquery(p_input->>'ts_query_web');
     v_tsquery_web_with_prefix_matching tsquery;
 begin
@@ -25,6 +32,14 @@ begin
         select array_agg(e::text) into v_maturity
         from jsonb_array_elements_text(p_input->'maturity') e;
     end if;
+    if p_input ? 'category' and p_input->'category' <> 'null' then
+        select array_agg(e::text) into v_category
+        from jsonb_array_elements_text(p_input->'category') e;
+    end if;
+    if p_input ? 'subcategory' and p_input->'subcategory' <> 'null' then
+        select array_agg(e::text) into v_subcategory
+        from jsonb_array_elements_text(p_input->'subcategory') e;
+    end if;
     if p_input ? 'project' and p_input->'project' <> 'null' then
         select array_agg(e::text) into v_project
         from jsonb_array_elements_text(p_input->'project') e;
@@ -41,10 +56,26 @@ begin
         select array_agg(e::text) into v_difficulty
         from jsonb_array_elements_text(p_input->'difficulty') e;
     end if;
+    if p_input ? 'estimated_difficulty' and p_input->'estimated_difficulty' <> 'null' then
+        select array_agg(e::text) into v_estimated_difficulty
+        from jsonb_array_elements_text(p_input->'estimated_difficulty') e;
+    end if;
     if p_input ? 'language' and p_input->'language' <> 'null' then
         select array_agg(e::text) into v_language
         from jsonb_array_elements_text(p_input->'language') e;
     end if;
+    if p_input ? 'primary_language' and p_input->'primary_language' <> 'null' then
+        select array_agg(e::text) into v_primary_language
+        from jsonb_array_elements_text(p_input->'primary_language') e;
+    end if;
+    if p_input ? 'skill' and p_input->'skill' <> 'null' then
+        select array_agg(e::text) into v_skill
+        from jsonb_array_elements_text(p_input->'skill') e;
+    end if;
+    if p_input ? 'type' and p_input->'type' <> 'null' then
+        select array_agg(e::text) into v_type
+        from jsonb_array_elements_text(p_input->'type') e;
+    end if;
 
     -- Prepare v_tsquery_web_with_prefix_matching
     if v_tsquery_web is not null then
@@ -61,8 +92,10 @@ begin
     end if;
 
     return query
-    with filtered_issues as (
+    with matching_issues as (
         select
+            i.issue_id,
+            i.canonical_issue_id,
             i.number as issue_number,
             i.title as issue_title,
             i.url as issue_url,
@@ -71,22 +104,47 @@ begin
             i.area as issue_area,
             i.kind as issue_kind,
             i.difficulty as issue_difficulty,
+            i.estimated_difficulty as issue_estimated_difficulty,
             i.mentor_available as issue_mentor_available,
             i.good_first_issue as good_first_issue,
+            -- Repository languages are used when no skills were detected
+            coalesce(nullif(i.skills, '{}'), r.languages) as issue_skills,
             i.has_linked_prs as has_linked_prs,
+            i.type as issue_type,
+            (
+                select array_agg(d.url order by d.url asc)
+                from issue d
+                where d.canonical_issue_id = i.issue_id
+                and d.type = i.type
+            ) as issue_duplicates,
             r.name as repository_name,
             r.url as repository_url,
             r.homepage_url as repository_homepage_url,
+            r.contributing_url as repository_contributing_url,
+            r.code_of_conduct_url as repository_code_of_conduct_url,
+            r.license as repository_license,
+            r.default_branch as repository_default_branch,
+            r.archived as repository_archived,
+            r.disabled as repository_disabled,
+            r.last_commit_at as repository_last_commit_at,
+            r.median_first_response_secs as repository_median_first_response_secs,
+            r.open_pull_requests as repository_open_pull_requests,
             r.topics as repository_topics,
             r.languages as repository_languages,
+            r.language_shares as repository_language_shares,
+            r.primary_language as repository_primary_language,
             r.stars as repository_stars,
             p.name as project_name,
             p.display_name as project_display_name,
-            p.logo_url as project_logo_url,
+            coalesce(p.logo_url, p.enriched_social_preview_url) as project_logo_url,
             p.logo_dark_url as project_logo_dark_url,
             p.devstats_url as project_devstats_url,
+            p.enriched_homepage_url as project_homepage_url,
+            p.enriched_contributing_url as project_contributing_url,
             p.accepted_at as project_accepted_at,
             p.maturity as project_maturity,
+            p.category as project_category,
+            p.subcategory as project_subcategory,
             p.maintainers_wanted as project_maintainers_wanted,
             p.foundation_id as project_foundation,
             (
@@ -97,7 +155,10 @@ begin
         from issue i
         join repository r using (repository_id)
         join project p using (project_id)
-        where
+        join foundation f using (foundation_id)
+        where f.enabled = true
+        and p.deleted_at is null
+        and
             case when v_tsquery_web is not null then
                 v_tsquery_web_with_prefix_matching @@ i.tsdoc
             else true end
@@ -107,6 +168,12 @@ begin
         and
             case when cardinality(v_maturity) > 0 then
             p.maturity = any(v_maturity) else true end
+        and
+            case when cardinality(v_category) > 0 then
+            p.category = any(v_category) else true end
+        and
+            case when cardinality(v_subcategory) > 0 then
+            p.subcategory = any(v_subcategory) else true end
         and
             case when cardinality(v_project) > 0 then
             p.name = any(v_project) else true end
@@ -119,9 +186,34 @@ begin
         and
             case when cardinality(v_difficulty) > 0 then
             i.difficulty::text = any(v_difficulty) else true end
+        and
+            -- The explicit difficulty is used when available (issues with an
+            -- explicit difficulty don't have an estimated one)
+            case when cardinality(v_estimated_difficulty) > 0 then
+            coalesce(i.difficulty, i.estimated_difficulty)::text = any(v_estimated_difficulty) else true end
         and
             case when cardinality(v_language) > 0 then
             r.languages && v_language else true end
+        and
+            -- When a minimum share is provided, at least one of the languages
+            -- selected must reach it (shares are percentages)
+            case when cardinality(v_language) > 0 and v_language_min_share is not null then
+                exists (
+                    select 1
+                    from jsonb_each_text(r.language_shares) ls
+                    where ls.key = any(v_language)
+                    and ls.value::real >= v_language_min_share
+                )
+            else true end
+        and
+            case when cardinality(v_primary_language) > 0 then
+            r.primary_language = any(v_primary_language) else true end
+        and
+            case when cardinality(v_skill) > 0 then
+            coalesce(nullif(i.skills, '{}'), r.languages) && v_skill else true end
+        and
+            case when cardinality(v_type) > 0 then
+            i.type::text = any(v_type) else true end
         and
             case when p_input ? 'mentor_available' and (p_input->>'mentor_available')::boolean = true then
                 i.mentor_available = true
@@ -134,6 +226,32 @@ begin
             case when p_input ? 'no_linked_prs' and (p_input->>'no_linked_prs')::boolean = true then
                 i.has_linked_prs = false
             else true end
+        and
+            -- Inactive repositories are the archived or disabled ones and
+            -- those without commits in the last year
+            case when p_input ? 'hide_inactive' and (p_input->>'hide_inactive')::boolean = true then
+                r.archived = false
+                and r.disabled = false
+                and (r.last_commit_at is null or r.last_commit_at > current_timestamp - '1 year'::interval)
+            else true end
+    ),
+    filtered_issues as (
+        select *
+        from matching_issues m
+        where
+            -- Duplicate issues are collapsed into their canonical issue (as
+            -- long as it matches the filters as well) unless requested
+            -- otherwise
+            case when p_input ? 'include_duplicates' and (p_input->>'include_duplicates')::boolean = true then
+                true
+            else
+                not exists (
+                    select 1
+                    from matching_issues c
+                    where c.issue_id = m.canonical_issue_id
+                    and c.issue_type = m.issue_type
+                )
+            end
     )
     select
         (
@@ -146,15 +264,30 @@ begin
                 'area', issue_area,
                 'kind', issue_kind,
                 'difficulty', issue_difficulty,
+                'estimated_difficulty', issue_estimated_difficulty,
                 'mentor_available', issue_mentor_available,
                 'good_first_issue', good_first_issue,
+                'skills', issue_skills,
                 'has_linked_prs', has_linked_prs,
+                'type', issue_type,
+                'duplicates', issue_duplicates,
                 'repository', json_build_object(
                     'name', repository_name,
                     'url', repository_url,
                     'homepage_url', repository_homepage_url,
+                    'contributing_url', repository_contributing_url,
+                    'code_of_conduct_url', repository_code_of_conduct_url,
+                    'license', repository_license,
+                    'default_branch', repository_default_branch,
+                    'archived', repository_archived,
+                    'disabled', repository_disabled,
+                    'last_commit_at', floor(extract(epoch from repository_last_commit_at)),
+                    'median_first_response_secs', repository_median_first_response_secs,
+                    'open_pull_requests', repository_open_pull_requests,
                     'topics', repository_topics,
                     'languages', repository_languages,
+                    'language_shares', repository_language_shares,
+                    'primary_language', repository_primary_language,
                     'stars', repository_stars
                 ),
                 'project', json_build_object(
@@ -163,8 +296,12 @@ begin
                     'logo_url', project_logo_url,
                     'logo_dark_url', project_logo_dark_url,
                     'devstats_url', project_devstats_url,
+                    'homepage_url', project_homepage_url,
+                    'contributing_url', project_contributing_url,
                     'accepted_at', project_accepted_at,
                     'maturity', project_maturity,
+                    'category', project_category,
+                    'subcategory', project_subcategory,
                     'maintainers_wanted', project_maintainers_wanted,
                     'foundation', project_foundation
                 ),
@@ -175,7 +312,9 @@ begin
                 from filtered_issues
                 order by
                     (case when v_sort_by = 'most_recent' then issue_published_at end) desc,
-                    (case when v_sort_by = 'relevance' then (relevance, issue_published_at)  end) desc
+                    (case when v_sort_by = 'relevance' then (relevance, issue_published_at)  end) desc,
+                    (case when v_sort_by = 'responsiveness' then repository_median_first_response_secs end) asc nulls last,
+                    (case when v_sort_by = 'responsiveness' then issue_published_at end) desc
                 limit v_limit
                 offset v_offset
             ) fp
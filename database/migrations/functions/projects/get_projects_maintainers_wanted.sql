@@ -0,0 +1,65 @@
+-- Return the projects looking for maintainers that match the input parameters
+-- provided in json format.
+create or replace function get_projects_maintainers_wanted(p_input jsonb)
+returns json as $$
+declare
+    v_foundation text[];
+    v_language text[];
+    v_projects json;
+begin
+    -- Prepare filters
+    if p_input ? 'foundation' and p_input->'foundation' <> 'null' then
+        select array_agg(e::text) into v_foundation
+        from jsonb_array_elements_text(p_input->'foundation') e;
+    end if;
+    if p_input ? 'language' and p_input->'language' <> 'null' then
+        select array_agg(e::text) into v_language
+        from jsonb_array_elements_text(p_input->'language') e;
+    end if;
+
+    select coalesce(json_agg(json_strip_nulls(json_build_object(
+        'name', p.name,
+        'display_name', p.display_name,
+        -- Data file values take precedence over the enrichment ones, except
+        -- for descriptions that just repeat the project's name, which some
+        -- data files use as a fallback (see Project::needs_enrichment in the
+        -- registrar, that applies the same rule)
+        'description', coalesce(
+            nullif(p.description, coalesce(p.display_name, p.name)),
+            p.enriched_description,
+            p.description
+        ),
+        'logo_url', coalesce(p.logo_url, p.enriched_social_preview_url),
+        'homepage_url', p.enriched_homepage_url,
+        'contributing_url', p.enriched_contributing_url,
+        'logo_dark_url', p.logo_dark_url,
+        'maturity', p.maturity,
+        'foundation', p.foundation_id,
+        'languages', (
+            select array_agg(distinct language order by language)
+            from repository r, unnest(r.languages) as language
+            where r.project_id = p.project_id
+        ),
+        'links', p.maintainers_wanted->'links',
+        'contacts', p.maintainers_wanted->'contacts'
+    )) order by p.foundation_id asc, p.name asc), '[]') into v_projects
+    from project p
+    join foundation f using (foundation_id)
+    where f.enabled = true
+    and p.deleted_at is null
+    and (p.maintainers_wanted->>'enabled')::boolean = true
+    and
+        case when cardinality(v_foundation) > 0 then
+        p.foundation_id = any(v_foundation) else true end
+    and
+        case when cardinality(v_language) > 0 then
+        exists (
+            select 1
+            from repository r
+            where r.project_id = p.project_id
+            and r.languages && v_language
+        ) else true end;
+
+    return v_projects;
+end
+$$ language plpgsql;
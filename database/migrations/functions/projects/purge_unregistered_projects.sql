@@ -0,0 +1,16 @@
+-- purge_unregistered_projects deletes permanently the projects of the provided
+-- foundation that were unregistered longer than the grace period provided ago,
+-- returning the number of projects deleted.
+create or replace function purge_unregistered_projects(
+    p_foundation_id text,
+    p_grace_period_days integer
+)
+returns integer as $$
+    with deleted as (
+        delete from project
+        where foundation_id = p_foundation_id
+        and deleted_at < current_timestamp - make_interval(days => p_grace_period_days)
+        returning 1
+    )
+    select count(*)::integer from deleted;
+$$ language sql;
@@ -4,8 +4,18 @@ returns void as $$
 declare
     v_project_id uuid;
     v_repository jsonb;
+    v_prev_category text;
+    v_prev_subcategory text;
+    v_enrichment_failed boolean := coalesce((p_project->>'enrichment_failed')::boolean, false);
 begin
-    -- Register project or update existing one
+    -- Get the project's current category and subcategory (if registered)
+    select category, subcategory into v_prev_category, v_prev_subcategory
+    from project
+    where foundation_id = p_foundation_id
+    and name = p_project->>'name';
+
+    -- Register project or update existing one (keeping the enrichment data
+    -- stored previously when the project could not be enriched this time)
     insert into project (
         name,
         display_name,
@@ -15,7 +25,13 @@ begin
         devstats_url,
         accepted_at,
         maturity,
+        category,
+        subcategory,
         maintainers_wanted,
+        enriched_description,
+        enriched_homepage_url,
+        enriched_social_preview_url,
+        enriched_contributing_url,
         digest,
         foundation_id
     ) values (
@@ -27,7 +43,13 @@ begin
         p_project->>'devstats_url',
         (p_project->>'accepted_at')::date,
         (p_project->>'maturity'),
+        nullif(p_project->>'category', ''),
+        nullif(p_project->>'subcategory', ''),
         p_project->'maintainers_wanted',
+        p_project->'enrichment'->>'description',
+        p_project->'enrichment'->>'homepage_url',
+        p_project->'enrichment'->>'social_preview_url',
+        p_project->'enrichment'->>'contributing_url',
         p_project->>'digest',
         p_foundation_id
     )
@@ -40,28 +62,74 @@ begin
         devstats_url = excluded.devstats_url,
         accepted_at = excluded.accepted_at,
         maturity = excluded.maturity,
+        category = excluded.category,
+        subcategory = excluded.subcategory,
         maintainers_wanted = excluded.maintainers_wanted,
-        digest = excluded.digest
+        enriched_description = case when v_enrichment_failed then project.enriched_description else excluded.enriched_description end,
+        enriched_homepage_url = case when v_enrichment_failed then project.enriched_homepage_url else excluded.enriched_homepage_url end,
+        enriched_social_preview_url = case when v_enrichment_failed then project.enriched_social_preview_url else excluded.enriched_social_preview_url end,
+        enriched_contributing_url = case when v_enrichment_failed then project.enriched_contributing_url else excluded.enriched_contributing_url end,
+        digest = excluded.digest,
+        deleted_at = null
     returning project_id into v_project_id;
 
+    -- Reset the digest of the project's issues when the category changes, so
+    -- that the tracker registers them again refreshing their search document
+    if v_prev_category is distinct from nullif(p_project->>'category', '')
+    or v_prev_subcategory is distinct from nullif(p_project->>'subcategory', '') then
+        update issue set digest = null
+        where repository_id in (
+            select repository_id from repository where project_id = v_project_id
+        );
+    end if;
+
     -- Register repositories or update existing ones
     for v_repository in select * from jsonb_array_elements(p_project->'repositories')
     loop
+        -- Keep the repository registered with the same GitHub node id when it
+        -- has been renamed, updating its url instead of registering it again
+        if v_repository->>'github_node_id' is not null then
+            update repository set
+                name = v_repository->>'name',
+                url = v_repository->>'url',
+                updated_at = current_timestamp
+            where repository_id = (
+                select repository_id
+                from repository
+                where project_id = v_project_id
+                and github_node_id = v_repository->>'github_node_id'
+                and url not in (
+                    select value->>'url'
+                    from jsonb_array_elements(p_project->'repositories')
+                )
+                limit 1
+            );
+        end if;
+
         insert into repository (
             name,
             url,
             issues_filter_label,
+            label_overrides,
+            docs_only,
+            github_node_id,
             project_id
         ) values (
             v_repository->>'name',
             v_repository->>'url',
             nullif(v_repository->>'issues_filter_label', ''),
+            nullif(v_repository->'label_overrides', '{}'),
+            coalesce((v_repository->>'docs_only')::boolean, false),
+            v_repository->>'github_node_id',
             v_project_id
         )
         on conflict (project_id, url) do update
         set
             name = excluded.name,
             issues_filter_label = excluded.issues_filter_label,
+            label_overrides = excluded.label_overrides,
+            docs_only = excluded.docs_only,
+            github_node_id = coalesce(excluded.github_node_id, repository.github_node_id),
             updated_at = current_timestamp;
     end loop;
 
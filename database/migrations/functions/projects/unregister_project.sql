@@ -1,7 +1,10 @@
--- unregister_project unregisters the provided project from the database.
+-- unregister_project unregisters the provided project from the database. The
+-- project is only marked as deleted, it will be deleted permanently by
+-- purge_unregistered_projects once the grace period has passed.
 create or replace function unregister_project(p_foundation_id text, p_project_name text)
 returns void as $$
-    delete from project
+    update project set deleted_at = current_timestamp
     where foundation_id = p_foundation_id
-    and name = p_project_name;
+    and name = p_project_name
+    and deleted_at is null;
 $$ language sql;
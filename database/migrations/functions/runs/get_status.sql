@@ -0,0 +1,54 @@
+-- Return the status of the latest tracker and registrar runs, as well as the
+-- inactive (archived or disabled) repositories of each foundation, in json
+-- format.
+create or replace function get_status()
+returns json as $$
+    select json_build_object(
+        'tracker', (
+            select json_strip_nulls(json_build_object(
+                'started_at', floor(extract(epoch from started_at)),
+                'finished_at', floor(extract(epoch from finished_at)),
+                'status', status,
+                'processed', processed,
+                'errors', errors
+            ))
+            from run
+            where kind = 'tracker'
+            order by finished_at desc
+            limit 1
+        ),
+        'foundations', (
+            select coalesce(json_agg(json_strip_nulls(json_build_object(
+                'foundation', f.foundation_id,
+                'display_name', f.display_name,
+                'registrar', (
+                    select json_build_object(
+                        'started_at', floor(extract(epoch from started_at)),
+                        'finished_at', floor(extract(epoch from finished_at)),
+                        'status', status,
+                        'processed', processed,
+                        'errors', errors,
+                        'failures', failures
+                    )
+                    from run
+                    where kind = 'registrar'
+                    and foundation_id = f.foundation_id
+                    order by finished_at desc
+                    limit 1
+                ),
+                'inactive_repositories', (
+                    select json_agg(json_build_object(
+                        'url', r.url,
+                        'archived', r.archived,
+                        'disabled', r.disabled
+                    ) order by r.url asc)
+                    from repository r
+                    join project p using (project_id)
+                    where p.foundation_id = f.foundation_id
+                    and (r.archived = true or r.disabled = true)
+                )
+            )) order by f.foundation_id asc), '[]')
+            from foundation f
+        )
+    );
+$$ language sql;
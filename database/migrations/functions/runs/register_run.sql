@@ -0,0 +1,33 @@
+-- register_run registers the provided run in the database, removing old runs
+-- that are no longer needed. Failures are errors that affected only some of
+-- the items processed, so they do not make the run fail.
+create or replace function register_run(
+    p_kind text,
+    p_foundation_id text,
+    p_started_at timestamptz,
+    p_processed integer,
+    p_errors text[],
+    p_failures text[]
+)
+returns void as $$
+    insert into run (
+        kind,
+        status,
+        processed,
+        errors,
+        failures,
+        started_at,
+        foundation_id
+    ) values (
+        p_kind,
+        case when cardinality(p_errors) > 0 then 'failed' else 'succeeded' end,
+        p_processed,
+        nullif(p_errors, '{}'),
+        nullif(p_failures, '{}'),
+        p_started_at,
+        p_foundation_id
+    );
+
+    delete from run
+    where finished_at < current_timestamp - '30 days'::interval;
+$$ language sql;
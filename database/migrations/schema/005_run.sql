@@ -0,0 +1,17 @@
+create table if not exists run (
+    run_id uuid primary key default gen_random_uuid(),
+    kind text not null check (kind in ('registrar', 'tracker')),
+    status text not null check (status in ('succeeded', 'failed')),
+    processed integer not null default 0,
+    errors text[],
+    started_at timestamptz not null,
+    finished_at timestamptz not null default current_timestamp,
+    foundation_id text references foundation on delete cascade
+);
+
+create index run_kind_finished_at_idx on run (kind, finished_at desc);
+create index run_foundation_id_idx on run (foundation_id);
+
+---- create above / drop below ----
+
+drop table if exists run;
@@ -0,0 +1,13 @@
+create type issue_type as enum ('issue', 'pull_request');
+
+alter table issue add column type issue_type not null default 'issue';
+alter table issue drop constraint issue_pkey;
+alter table issue add primary key (issue_id, type);
+
+---- create above / drop below ----
+
+delete from issue where type <> 'issue';
+alter table issue drop constraint issue_pkey;
+alter table issue add primary key (issue_id);
+alter table issue drop column type;
+drop type if exists issue_type;
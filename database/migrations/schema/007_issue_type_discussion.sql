@@ -0,0 +1,12 @@
+alter type issue_type add value 'discussion';
+
+---- create above / drop below ----
+
+delete from issue where type = 'discussion';
+alter type issue_type rename to issue_type_old;
+create type issue_type as enum ('issue', 'pull_request');
+alter table issue
+    alter column type drop default,
+    alter column type type issue_type using type::text::issue_type,
+    alter column type set default 'issue';
+drop type issue_type_old;
@@ -0,0 +1,8 @@
+create type data_format as enum ('clomonitor', 'json', 'landscape');
+
+alter table foundation add column data_format data_format not null default 'clomonitor';
+
+---- create above / drop below ----
+
+alter table foundation drop column data_format;
+drop type data_format;
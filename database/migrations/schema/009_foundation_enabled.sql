@@ -0,0 +1,5 @@
+alter table foundation add column enabled boolean not null default true;
+
+---- create above / drop below ----
+
+alter table foundation drop column enabled;
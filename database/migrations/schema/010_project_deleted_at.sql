@@ -0,0 +1,5 @@
+alter table project add column deleted_at timestamptz;
+
+---- create above / drop below ----
+
+alter table project drop column deleted_at;
@@ -0,0 +1,11 @@
+alter table foundation
+    add column data_etag text,
+    add column data_last_modified text,
+    add column data_digest text;
+
+---- create above / drop below ----
+
+alter table foundation
+    drop column data_etag,
+    drop column data_last_modified,
+    drop column data_digest;
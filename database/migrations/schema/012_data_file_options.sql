@@ -0,0 +1,13 @@
+alter table foundation add column opt_in boolean not null default false;
+
+alter table repository
+    add column label_overrides jsonb,
+    add column docs_only boolean not null default false;
+
+---- create above / drop below ----
+
+alter table foundation drop column opt_in;
+
+alter table repository
+    drop column label_overrides,
+    drop column docs_only;
@@ -0,0 +1,9 @@
+alter table project
+    add column category text check (category <> ''),
+    add column subcategory text check (subcategory <> '');
+
+---- create above / drop below ----
+
+alter table project
+    drop column category,
+    drop column subcategory;
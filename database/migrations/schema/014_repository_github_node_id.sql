@@ -0,0 +1,7 @@
+alter table repository add column github_node_id text check (github_node_id <> '');
+
+create index repository_github_node_id_idx on repository (github_node_id);
+
+---- create above / drop below ----
+
+alter table repository drop column github_node_id;
@@ -0,0 +1,7 @@
+alter table run add column failures text[];
+
+drop function if exists register_run(text, text, timestamptz, integer, text[]);
+
+---- create above / drop below ----
+
+alter table run drop column failures;
@@ -0,0 +1,11 @@
+alter table project add column enriched_description text check (enriched_description <> '');
+alter table project add column enriched_homepage_url text check (enriched_homepage_url <> '');
+alter table project add column enriched_social_preview_url text check (enriched_social_preview_url <> '');
+alter table project add column enriched_contributing_url text check (enriched_contributing_url <> '');
+
+---- create above / drop below ----
+
+alter table project drop column enriched_description;
+alter table project drop column enriched_homepage_url;
+alter table project drop column enriched_social_preview_url;
+alter table project drop column enriched_contributing_url;
@@ -0,0 +1,11 @@
+alter table repository add column contributing_url text check (contributing_url <> '');
+alter table repository add column code_of_conduct_url text check (code_of_conduct_url <> '');
+alter table repository add column license text check (license <> '');
+alter table repository add column default_branch text check (default_branch <> '');
+
+---- create above / drop below ----
+
+alter table repository drop column contributing_url;
+alter table repository drop column code_of_conduct_url;
+alter table repository drop column license;
+alter table repository drop column default_branch;
@@ -0,0 +1,13 @@
+alter table repository add column archived boolean not null default false;
+alter table repository add column last_commit_at timestamptz;
+alter table repository add column median_first_response_secs bigint;
+alter table repository add column open_pull_requests integer;
+
+create index repository_last_commit_at_idx on repository (last_commit_at);
+
+---- create above / drop below ----
+
+alter table repository drop column archived;
+alter table repository drop column last_commit_at;
+alter table repository drop column median_first_response_secs;
+alter table repository drop column open_pull_requests;
@@ -0,0 +1,11 @@
+alter table repository add column language_sizes jsonb;
+alter table repository add column language_shares jsonb;
+alter table repository add column primary_language text;
+
+create index repository_primary_language_idx on repository (primary_language);
+
+---- create above / drop below ----
+
+alter table repository drop column language_sizes;
+alter table repository drop column language_shares;
+alter table repository drop column primary_language;
@@ -0,0 +1,7 @@
+alter table issue add column skills text[] not null default '{}';
+
+create index issue_skills_idx on issue using gin (skills);
+
+---- create above / drop below ----
+
+alter table issue drop column skills;
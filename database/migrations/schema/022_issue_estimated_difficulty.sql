@@ -0,0 +1,5 @@
+alter table issue add column estimated_difficulty difficulty;
+
+---- create above / drop below ----
+
+alter table issue drop column estimated_difficulty;
@@ -0,0 +1,9 @@
+create extension if not exists pg_trgm;
+
+alter table issue add column canonical_issue_id bigint;
+
+create index issue_canonical_issue_id_idx on issue (canonical_issue_id);
+
+---- create above / drop below ----
+
+alter table issue drop column canonical_issue_id;
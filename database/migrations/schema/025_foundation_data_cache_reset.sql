@@ -0,0 +1,9 @@
+-- Reset the data file cache of all foundations, so that projects registered
+-- before the category and subcategory were stored are processed again
+update foundation set
+    data_etag = null,
+    data_last_modified = null,
+    data_digest = null,
+    data_cache_version = null;
+
+---- create above / drop below ----
@@ -0,0 +1,13 @@
+alter table issue add column normalized_title text generated always as (
+    trim(regexp_replace(lower(title), '[^[:alnum:]]+', ' ', 'g'))
+) stored;
+
+create index issue_normalized_title_idx on issue using gin (normalized_title gin_trgm_ops);
+
+-- Repositories whose issues changed since duplicates were last marked
+alter table repository add column issues_changed boolean not null default true;
+
+---- create above / drop below ----
+
+alter table issue drop column normalized_title;
+alter table repository drop column issues_changed;
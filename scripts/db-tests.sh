@@ -0,0 +1,29 @@
+#!/bin/sh
+
+# Runs the database integration tests against a throwaway PostgreSQL server.
+# Requires initdb, pg_ctl, psql and tern to be available in the PATH.
+
+set -e
+
+PORT=${DB_TESTS_PORT:-55432}
+TMP_DIR=$(mktemp -d)
+trap 'pg_ctl -D $TMP_DIR/data -m immediate stop >/dev/null 2>&1; rm -rf $TMP_DIR' EXIT
+
+echo "- Starting database server.."
+initdb -D $TMP_DIR/data -A trust -U postgres >/dev/null
+pg_ctl -D $TMP_DIR/data -o "-p $PORT -k $TMP_DIR -c listen_addresses=''" -l $TMP_DIR/postgres.log -w start >/dev/null
+psql -h $TMP_DIR -p $PORT -U postgres -q -c "create database clotributor_tests"
+echo "Done"
+
+cat > $TMP_DIR/tern.conf <<TERN_CONF
+[database]
+host = $TMP_DIR
+port = $PORT
+database = clotributor_tests
+user = postgres
+TERN_CONF
+(cd database/migrations && TERN_CONF=$TMP_DIR/tern.conf ./migrate.sh)
+
+echo "- Running database tests.."
+export CLOTRIBUTOR_TESTS_DB_URL="host=$TMP_DIR port=$PORT user=postgres dbname=clotributor_tests"
+cargo test --workspace -- --ignored db::tests
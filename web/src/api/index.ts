@@ -141,6 +141,10 @@ class API_CLASS {
       q += '&no_linked_prs=true';
     }
 
+    if (query.hide_inactive) {
+      q += '&hide_inactive=true';
+    }
+
     if (!isUndefined(query.filters) && !isEmpty(query.filters)) {
       Object.keys(query.filters!).forEach((k: string) => {
         query.filters![k].forEach((f: string, index: number) => {
@@ -16,6 +16,10 @@ export const SORT_OPTIONS = [
     label: 'Relevance',
     by: SortBy.Relevance,
   },
+  {
+    label: 'Responsiveness',
+    by: SortBy.Responsiveness,
+  },
 ];
 
 export const FILTER_CATEGORY_NAMES = {
@@ -10,7 +10,7 @@ import { format, fromUnixTime, parseISO } from 'date-fns';
 import { isUndefined } from 'lodash';
 import { useContext, useEffect, useState } from 'react';
 import { BsDot } from 'react-icons/bs';
-import { FaChartBar, FaGithub } from 'react-icons/fa';
+import { FaBookOpen, FaChartBar, FaGithub, FaHandshake } from 'react-icons/fa';
 import { FiExternalLink, FiStar } from 'react-icons/fi';
 import { GoCalendar } from 'react-icons/go';
 import { IoGlobeOutline } from 'react-icons/io5';
@@ -36,6 +36,8 @@ const Card = (props: Props) => {
   const [availableTopics, setAvailableTopics] = useState<string[]>([]);
   const isMaintainersWantedAvailable: boolean =
     !isUndefined(props.issue.project.maintainers_wanted) && props.issue.project.maintainers_wanted.enabled;
+  const contributingUrl: string | undefined =
+    props.issue.repository.contributing_url || props.issue.project.contributing_url;
 
   const getExtraFilter = () => {
     if (isEmbed) {
@@ -258,6 +260,26 @@ const Card = (props: Props) => {
                       </ExternalLink>
                     )}
 
+                    {contributingUrl && (
+                      <ExternalLink label="Contributing guidelines" href={contributingUrl} className="me-3">
+                        <div className={`d-flex flex-row align-items-center text-muted ${styles.link}`}>
+                          <FaBookOpen className={styles.statsIcon} />
+                        </div>
+                      </ExternalLink>
+                    )}
+
+                    {props.issue.repository.code_of_conduct_url && (
+                      <ExternalLink
+                        label="Code of conduct"
+                        href={props.issue.repository.code_of_conduct_url}
+                        className="me-3"
+                      >
+                        <div className={`d-flex flex-row align-items-center text-muted ${styles.link}`}>
+                          <FaHandshake className={styles.statsIcon} />
+                        </div>
+                      </ExternalLink>
+                    )}
+
                     {props.issue.project.accepted_at && (
                       <div
                         className={`d-flex flex-row align-items-center ${styles.subtitle} ${styles.wrapperCalendar}`}
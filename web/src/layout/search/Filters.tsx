@@ -17,6 +17,7 @@ interface Props {
   mentorAvailable: boolean;
   goodFirstIssue: boolean;
   noLinkedPRs: boolean;
+  hideInactive: boolean;
   onChange: (name: string, value: string, checked: boolean, type?: string) => void;
   onResetFilters?: () => void;
   device: string;
@@ -38,6 +39,9 @@ const Filters = (props: Props) => {
     if (props.noLinkedPRs) {
       otherFilters.push('no_linked_prs');
     }
+    if (props.hideInactive) {
+      otherFilters.push('hide_inactive');
+    }
     return otherFilters;
   };
 
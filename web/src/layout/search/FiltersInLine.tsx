@@ -24,6 +24,7 @@ interface Props {
   mentorAvailable: boolean;
   goodFirstIssue: boolean;
   noLinkedPRs: boolean;
+  hideInactive: boolean;
   onChange: (name: string, value: string, checked: boolean, type?: string) => void;
   onResetFilters: () => void;
   isLoadingFilters?: boolean;
@@ -102,6 +103,9 @@ const FiltersInLine = (props: Props) => {
     if (props.noLinkedPRs) {
       otherFilters.push('no_linked_prs');
     }
+    if (props.hideInactive) {
+      otherFilters.push('hide_inactive');
+    }
     return otherFilters;
   };
 
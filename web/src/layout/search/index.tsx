@@ -42,6 +42,7 @@ const Search = () => {
   const [mentorAvailable, setMentorAvailable] = useState<boolean>(false);
   const [goodFirstIssue, setGoodFirstIssue] = useState<boolean>(false);
   const [noLinkedPRs, setNoLinkedPRs] = useState<boolean>(false);
+  const [hideInactive, setHideInactive] = useState<boolean>(false);
   const [fullFilters, setFullFilters] = useState<FilterSection[] | undefined>(undefined);
   const [cleanFilters, setCleanFilters] = useState<FilterSection[] | undefined>(undefined);
   const [filters, setFilters] = useState<FilterSection[] | undefined>(undefined);
@@ -54,12 +55,13 @@ const Search = () => {
   const [apiError, setApiError] = useState<string | null>(null);
   const [selectedFoundation, setSelectedFoundation] = useState<Foundation | null>(null);
   // Check if some filters are active
-  let ifActiveFilters = !isEmpty(activeFilters) || mentorAvailable || goodFirstIssue;
+  let ifActiveFilters = !isEmpty(activeFilters) || mentorAvailable || goodFirstIssue || hideInactive;
   if (isEmbed) {
     const filtersWithoutFoundation = { ...activeFilters };
     delete filtersWithoutFoundation[FilterKind.Foundation];
 
-    ifActiveFilters = !isEmpty(filtersWithoutFoundation) || mentorAvailable || goodFirstIssue || noLinkedPRs;
+    ifActiveFilters =
+      !isEmpty(filtersWithoutFoundation) || mentorAvailable || goodFirstIssue || noLinkedPRs || hideInactive;
   }
 
   const getExtraFilter = () => {
@@ -179,6 +181,7 @@ const Search = () => {
       mentor_available: mentorAvailable,
       good_first_issue: goodFirstIssue,
       no_linked_prs: noLinkedPRs,
+      hide_inactive: hideInactive,
       ts_query_web: text,
       filters: { ...activeFilters, ...getExtraFilter() },
     };
@@ -270,6 +273,7 @@ const Search = () => {
     setMentorAvailable(formattedParams.mentor_available || false);
     setGoodFirstIssue(formattedParams.good_first_issue || false);
     setNoLinkedPRs(formattedParams.no_linked_prs || false);
+    setHideInactive(formattedParams.hide_inactive || false);
     setActiveFilters(formattedParams.filters || {});
     setPageNumber(formattedParams.pageNumber);
 
@@ -299,6 +303,7 @@ const Search = () => {
           mentor_available: formattedParams.mentor_available || false,
           good_first_issue: formattedParams.good_first_issue || false,
           no_linked_prs: formattedParams.no_linked_prs || false,
+          hide_inactive: formattedParams.hide_inactive || false,
           sort_by: sort.by,
           filters: formattedParams.filters || {},
           offset: calculateOffset(formattedParams.pageNumber),
@@ -388,6 +393,7 @@ const Search = () => {
                       mentorAvailable={mentorAvailable}
                       goodFirstIssue={goodFirstIssue}
                       noLinkedPRs={noLinkedPRs}
+                      hideInactive={hideInactive}
                       onChange={onFiltersChange}
                       visibleTitle={false}
                     />
@@ -438,6 +444,7 @@ const Search = () => {
               mentorAvailable={mentorAvailable}
               goodFirstIssue={goodFirstIssue}
               noLinkedPRs={noLinkedPRs}
+              hideInactive={hideInactive}
               isLoadingFilters={isUndefined(filters)}
               device="desktop"
               ifActiveFilters={ifActiveFilters}
@@ -24,8 +24,11 @@ export interface Issue {
   kind?: string;
   area?: string;
   difficulty?: string;
+  estimated_difficulty?: string;
   mentor_available?: string;
   good_first_issue?: string;
+  skills?: string[];
+  duplicates?: string[];
   has_linked_prs?: boolean;
   project: Project;
   repository: Repository;
@@ -53,8 +56,12 @@ export interface Project {
   logo_url?: string;
   logo_dark_url?: string;
   devstats_url?: string;
+  homepage_url?: string;
+  contributing_url?: string;
   accepted_at?: string;
   maturity?: Maturity;
+  category?: string;
+  subcategory?: string;
   foundation: Foundation;
   maintainers_wanted?: MaintainersWanted;
 }
@@ -62,25 +69,40 @@ export interface Project {
 export interface Repository {
   name: string;
   homepage_url?: string;
+  contributing_url?: string;
+  code_of_conduct_url?: string;
+  license?: string;
+  default_branch?: string;
+  archived: boolean;
+  disabled: boolean;
+  last_commit_at?: number;
+  median_first_response_secs?: number;
+  open_pull_requests?: number;
   url: string;
   topics: string[];
   languages: string[];
+  language_shares?: { [key: string]: number };
+  primary_language?: string;
   stars: number;
 }
 
 export enum FilterKind {
   Foundation = 'foundation',
   Maturity = 'maturity',
+  Category = 'category',
+  Subcategory = 'subcategory',
   Project = 'project',
   Kind = 'kind',
   Difficulty = 'difficulty',
   Area = 'area',
   Language = 'language',
+  Type = 'type',
 }
 
 export enum SortBy {
   MostRecent = 'most_recent',
   Relevance = 'relevance',
+  Responsiveness = 'responsiveness',
 }
 
 export interface Error {
@@ -101,6 +123,7 @@ export interface BasicQuery {
   mentor_available?: boolean;
   good_first_issue?: boolean;
   no_linked_prs?: boolean;
+  hide_inactive?: boolean;
 }
 
 export interface SearchQuery extends BasicQuery {
@@ -0,0 +1,34 @@
+import buildSearchParams from './buildSearchParams';
+
+describe('buildSearchParams', () => {
+  it('returns the whitelisted filters', () => {
+    const params = new URLSearchParams(
+      'ts_query_web=test&foundation=cncf&language=Go&type=pull_request&type=discussion&unknown=value&page=2'
+    );
+    expect(buildSearchParams(params)).toStrictEqual({
+      ts_query_web: 'test',
+      mentor_available: false,
+      good_first_issue: false,
+      no_linked_prs: false,
+      hide_inactive: false,
+      filters: {
+        foundation: ['cncf'],
+        language: ['Go'],
+        type: ['pull_request', 'discussion'],
+      },
+      pageNumber: 2,
+    });
+  });
+
+  it('returns the default values when no params are provided', () => {
+    expect(buildSearchParams(new URLSearchParams(''))).toStrictEqual({
+      ts_query_web: undefined,
+      mentor_available: false,
+      good_first_issue: false,
+      no_linked_prs: false,
+      hide_inactive: false,
+      filters: {},
+      pageNumber: 1,
+    });
+  });
+});
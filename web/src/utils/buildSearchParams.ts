@@ -9,11 +9,14 @@ interface F {
 const WHITELISTED_FILTER_KEYS = [
   FilterKind.Foundation, // Project foundation
   FilterKind.Maturity, // Project maturity
+  FilterKind.Category, // Project category
+  FilterKind.Subcategory, // Project subcategory
   FilterKind.Project, // Project name
   FilterKind.Kind, // Issue kind
   FilterKind.Difficulty, // Issue difficulty
   FilterKind.Area, // Issue area
   FilterKind.Language, // Issue language
+  FilterKind.Type, // Issue type
 ];
 
 const buildSearchParams = (p: URLSearchParams): SearchFiltersURL => {
@@ -32,6 +35,7 @@ const buildSearchParams = (p: URLSearchParams): SearchFiltersURL => {
     mentor_available: p.has('mentor_available'),
     good_first_issue: p.has('good_first_issue'),
     no_linked_prs: p.has('no_linked_prs'),
+    hide_inactive: p.has('hide_inactive'),
     filters: { ...filters },
     pageNumber: p.has('page') && !isNull(p.get('page')) ? parseInt(p.get('page')!) : 1,
   };
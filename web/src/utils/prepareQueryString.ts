@@ -23,6 +23,9 @@ const getURLSearchParams = (query: BasicQuery): URLSearchParams => {
   if (!isUndefined(query.no_linked_prs) && query.no_linked_prs) {
     q.set('no_linked_prs', 'true');
   }
+  if (!isUndefined(query.hide_inactive) && query.hide_inactive) {
+    q.set('hide_inactive', 'true');
+  }
   return q;
 };
 